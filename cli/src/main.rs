@@ -1,48 +1,829 @@
 use {
-    clap::{crate_description, crate_name, crate_version, Arg, Command},
+    clap::{crate_description, crate_name, crate_version, Arg, ArgMatches, Command},
+    num_traits::FromPrimitive,
+    serde::Serialize,
+    sol_rewards_vault_program::{
+        error::RewardsVaultError,
+        event::RewardsVaultEvent,
+        state::{RewardsVaultState, MAX_SPLIT_RECIPIENTS},
+    },
+    solana_account_decoder::UiAccountEncoding,
     solana_clap_v3_utils::{
-        input_parsers::{pubkey_of, signer_of},
+        input_parsers::{lamports_of_sol, pubkey_of, value_of},
         input_validators::{
-            is_url_or_moniker, is_valid_pubkey, is_valid_signer, normalize_to_url_if_moniker,
+            is_amount, is_url_or_moniker, is_valid_percentage, is_valid_pubkey, is_valid_signer,
+            normalize_to_url_if_moniker,
+        },
+        keypair::{signer_from_path_with_config, DefaultSigner, SignerFromPathConfig},
+        nonce::{NonceArgs, NONCE_ARG, NONCE_AUTHORITY_ARG},
+        offline::{OfflineArgs, BLOCKHASH_ARG, DUMP_TRANSACTION_MESSAGE, SIGN_ONLY_ARG},
+    },
+    solana_client::{
+        client_error::ClientError,
+        nonblocking::{nonce_utils, rpc_client::RpcClient},
+        rpc_config::{
+            RpcAccountInfoConfig, RpcGetVoteAccountsConfig, RpcProgramAccountsConfig,
+            RpcTransactionConfig,
         },
-        keypair::DefaultSigner,
+        rpc_filter::{Memcmp, RpcFilterType},
     },
-    solana_client::nonblocking::rpc_client::RpcClient,
     solana_remote_wallet::remote_wallet::RemoteWalletManager,
     solana_sdk::{
-        commitment_config::CommitmentConfig, message::Message,
-        signers::Signers, transaction::Transaction,
+        account::Account,
+        commitment_config::CommitmentConfig,
+        hash::Hash,
+        instruction::{Instruction, InstructionError},
+        message::Message,
+        native_token::Sol,
+        pubkey::Pubkey,
+        signature::{Signature, Signer},
+        transaction::{Transaction, TransactionError},
+        vote::{self, state::VoteState},
     },
-    std::{process::exit, sync::Arc},
+    solana_transaction_status::UiTransactionEncoding,
+    std::{collections::HashMap, process::exit, str::FromStr, sync::Arc},
 };
 
-async fn send_message<T: Signers>(
+/// Decodes the `RewardsVaultError` returned when a rewards vault instruction in `transaction` failed
+fn decode_rewards_vault_error(
+    program_id: &Pubkey,
+    transaction: &Transaction,
+    err: &ClientError,
+) -> Option<RewardsVaultError> {
+    match err.get_transaction_error()? {
+        TransactionError::InstructionError(index, InstructionError::Custom(code)) => {
+            let instruction = transaction.message.instructions.get(usize::from(index))?;
+            if instruction.program_id(&transaction.message.account_keys) != program_id {
+                return None;
+            }
+            RewardsVaultError::from_u32(code)
+        }
+        _ => None,
+    }
+}
+
+/// Name of the argument that prints the transaction message instead of signing and sending it
+const DUMP_MESSAGE_ARG: &str = "dump_message";
+
+fn dump_message_arg<'a>() -> Arg<'a> {
+    Arg::new(DUMP_MESSAGE_ARG)
+        .long("dump-message")
+        .value_name("ENCODING")
+        .takes_value(true)
+        .possible_values(["base58", "base64"])
+        .conflicts_with(SIGN_ONLY_ARG.name)
+        .help(
+            "Print the serialized transaction message in this encoding instead of signing and \
+             sending it, for example to propose it to a multisig. Without --blockhash or --nonce \
+             the latest blockhash is fetched from the cluster",
+        )
+}
+
+/// Returns whether the transaction message is dumped instead of signed. Not every subcommand takes
+/// `--dump-message`, so the fee payer is resolved with this fallible lookup as well
+fn is_dump_message(arg_matches: &ArgMatches) -> bool {
+    arg_matches
+        .try_contains_id(DUMP_MESSAGE_ARG)
+        .unwrap_or(false)
+}
+
+/// Returns whether the transaction is only signed or its message dumped, in which case the cluster
+/// may be unreachable and the vault is not read from it
+fn is_offline(arg_matches: &ArgMatches) -> bool {
+    arg_matches.is_present(SIGN_ONLY_ARG.name) || is_dump_message(arg_matches)
+}
+
+/// Returns the signer of argument `name` and its address. An address is accepted in place of a
+/// keypair when the transaction message is only dumped, as for a multisig authority that signs
+/// elsewhere
+#[allow(clippy::type_complexity)]
+fn signer_or_address_of(
+    matches: &ArgMatches,
+    name: &str,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<(Option<Box<dyn Signer>>, Option<Pubkey>), Box<dyn std::error::Error>> {
+    match matches.value_of(name) {
+        Some(location) => {
+            let signer = signer_from_path_with_config(
+                matches,
+                location,
+                name,
+                wallet_manager,
+                &SignerFromPathConfig {
+                    allow_null_signer: is_dump_message(matches),
+                },
+            )?;
+            let pubkey = signer.pubkey();
+            Ok((Some(signer), Some(pubkey)))
+        }
+        None => Ok((None, None)),
+    }
+}
+
+/// Builds, signs and sends a transaction of `instructions`. The first of `signers` pays the fees
+async fn send_message(
     rpc_client: &RpcClient,
-    message: Message,
-    signers: &T,
+    program_id: &Pubkey,
+    arg_matches: &ArgMatches,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+    instructions: &[Instruction],
+    mut signers: Vec<Box<dyn Signer>>,
 ) -> Result<(), String> {
+    let fee_payer = signers[0].pubkey();
+    let nonce_account = pubkey_of(arg_matches, NONCE_ARG.name);
+
+    let message = match nonce_account {
+        Some(nonce_account) => {
+            let nonce_authority =
+                match signer_or_address_of(arg_matches, NONCE_AUTHORITY_ARG.name, wallet_manager)
+                    .map_err(|err| format!("error: invalid nonce authority: {}", err))?
+                {
+                    (Some(nonce_authority_signer), Some(nonce_authority)) => {
+                        signers.push(nonce_authority_signer);
+                        nonce_authority
+                    }
+                    _ => fee_payer,
+                };
+            Message::new_with_nonce(
+                instructions.to_vec(),
+                Some(&fee_payer),
+                &nonce_account,
+                &nonce_authority,
+            )
+        }
+        None => Message::new(instructions, Some(&fee_payer)),
+    };
     let mut transaction = Transaction::new_unsigned(message);
 
-    let blockhash = rpc_client
-        .get_latest_blockhash()
-        .await
-        .map_err(|err| format!("error: unable to get latest blockhash: {}", err))?;
+    let blockhash = match (
+        value_of::<Hash>(arg_matches, BLOCKHASH_ARG.name),
+        nonce_account,
+    ) {
+        (Some(blockhash), _) => blockhash,
+        (None, Some(nonce_account)) => {
+            let account = nonce_utils::get_account_with_commitment(
+                rpc_client,
+                &nonce_account,
+                rpc_client.commitment(),
+            )
+            .await
+            .map_err(|err| format!("error: unable to get nonce account: {}", err))?;
+            nonce_utils::data_from_account(&account)
+                .map_err(|err| format!("error: invalid nonce account: {}", err))?
+                .blockhash()
+        }
+        (None, None) => rpc_client
+            .get_latest_blockhash()
+            .await
+            .map_err(|err| format!("error: unable to get latest blockhash: {}", err))?,
+    };
+
+    if let Some(encoding) = arg_matches.value_of(DUMP_MESSAGE_ARG) {
+        transaction.message.recent_blockhash = blockhash;
+        let message_data = transaction.message_data();
+        println!(
+            "{}",
+            match encoding {
+                "base58" => bs58::encode(message_data).into_string(),
+                _ => base64::encode(message_data),
+            }
+        );
+        return Ok(());
+    }
+
+    if arg_matches.is_present(SIGN_ONLY_ARG.name) {
+        transaction
+            .try_partial_sign(&signers, blockhash)
+            .map_err(|err| format!("error: failed to sign transaction: {}", err))?;
+        print_sign_only(
+            &transaction,
+            arg_matches.is_present(DUMP_TRANSACTION_MESSAGE.name),
+        );
+        return Ok(());
+    }
 
     transaction
-        .try_sign(signers, blockhash)
+        .try_sign(&signers, blockhash)
         .map_err(|err| format!("error: failed to sign transaction: {}", err))?;
 
     let signature = rpc_client
         .send_and_confirm_transaction_with_spinner(&transaction)
         .await
-        .map_err(|err| format!("error: send transaction: {}", err))?;
+        .map_err(
+            |err| match decode_rewards_vault_error(program_id, &transaction, &err) {
+                Some(rewards_vault_error) => {
+                    format!("error: send transaction: {}: {}", rewards_vault_error, err)
+                }
+                None => format!("error: send transaction: {}", err),
+            },
+        )?;
 
     println!("Success: {}", signature);
     Ok(())
 }
 
+/// Prints the signatures of a transaction signed with `--sign-only`, for submission with
+/// `--blockhash` and `--signer` once every signer has signed
+fn print_sign_only(transaction: &Transaction, dump_transaction_message: bool) {
+    println!("Blockhash: {}", transaction.message.recent_blockhash);
+    if dump_transaction_message {
+        println!(
+            "Transaction Message: {}",
+            base64::encode(transaction.message_data())
+        );
+    }
+    let signers = transaction
+        .message
+        .signer_keys()
+        .into_iter()
+        .zip(transaction.signatures.iter());
+
+    let (present_signers, absent_signers): (Vec<_>, Vec<_>) =
+        signers.partition(|(_, signature)| **signature != Signature::default());
+    if !present_signers.is_empty() {
+        println!("Signers (Pubkey=Signature):");
+        for (signer, signature) in present_signers {
+            println!("  {}={}", signer, signature);
+        }
+    }
+    if !absent_signers.is_empty() {
+        println!("Absent Signers (Pubkey):");
+        for (signer, _) in absent_signers {
+            println!("  {}", signer);
+        }
+    }
+}
+
+async fn get_vault_state(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+    vote_account: &Pubkey,
+) -> Result<RewardsVaultState, String> {
+    let vault_address =
+        sol_rewards_vault_program::get_rewards_vault_address(program_id, vote_account);
+
+    let vault_account = rpc_client
+        .get_account_with_commitment(&vault_address, rpc_client.commitment())
+        .await
+        .map_err(|err| format!("error: unable to get vault account: {}", err))?
+        .value
+        .ok_or_else(|| {
+            format!(
+                "error: {} does not reside in its rewards vault",
+                vote_account
+            )
+        })?;
+
+    RewardsVaultState::unpack(&vault_account.data)
+        .map_err(|err| format!("error: invalid vault account data: {}", err))
+}
+
+async fn get_vote_account(
+    rpc_client: &RpcClient,
+    vote_account: &Pubkey,
+) -> Result<Account, String> {
+    rpc_client
+        .get_account_with_commitment(vote_account, rpc_client.commitment())
+        .await
+        .map_err(|err| format!("error: unable to get vote account: {}", err))?
+        .value
+        .ok_or_else(|| format!("error: vote account {} does not exist", vote_account))
+}
+
+/// Returns the lamports the rewards authority may currently withdraw from `vote_account`, within
+/// the per-epoch withdraw limit
+async fn get_withdrawable_lamports(
+    rpc_client: &RpcClient,
+    vote_account: &Pubkey,
+    vault_state: &RewardsVaultState,
+) -> Result<u64, String> {
+    let account = get_vote_account(rpc_client, vote_account).await?;
+
+    let rent_exempt_minimum = rpc_client
+        .get_minimum_balance_for_rent_exemption(account.data.len())
+        .await
+        .map_err(|err| format!("error: unable to get rent-exempt minimum: {}", err))?;
+    if account.lamports < rent_exempt_minimum {
+        return Err(format!(
+            "error: {} balance of {} is below its rent-exempt minimum of {}",
+            vote_account,
+            Sol(account.lamports),
+            Sol(rent_exempt_minimum)
+        ));
+    }
+
+    let withdrawable_lamports = account
+        .lamports
+        .saturating_sub(rent_exempt_minimum.saturating_add(vault_state.reserve_lamports));
+    if vault_state.max_withdraw_per_epoch == 0 {
+        return Ok(withdrawable_lamports);
+    }
+
+    // Withdrawals only count against the limit within the epoch they were made in
+    let epoch = rpc_client
+        .get_epoch_info()
+        .await
+        .map_err(|err| format!("error: unable to get epoch info: {}", err))?
+        .epoch;
+    let withdrawn_this_epoch = if vault_state.last_withdraw_epoch == epoch {
+        vault_state.withdrawn_this_epoch
+    } else {
+        0
+    };
+    Ok(withdrawable_lamports.min(
+        vault_state
+            .max_withdraw_per_epoch
+            .saturating_sub(withdrawn_this_epoch),
+    ))
+}
+
+/// Rewards vault and vote account status reported by the `show` subcommand
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CliRewardsVault {
+    vote_account: String,
+    vault_address: String,
+    version: u8,
+    original_withdraw_authority: String,
+    pending_withdraw_authority: Option<String>,
+    rewards_authority: String,
+    reserve_lamports: u64,
+    locked_rewards_recipient: Option<String>,
+    crank_enabled: bool,
+    max_withdraw_per_epoch: Option<u64>,
+    withdrawn_this_epoch: u64,
+    last_withdraw_epoch: u64,
+    split: Vec<CliSplitRecipient>,
+    total_rewards_withdrawn: Option<u64>,
+    withdraw_count: Option<u64>,
+    entered_epoch: Option<u64>,
+    entered_slot: Option<u64>,
+    vote_account_withdrawer: String,
+    vote_account_in_vault: bool,
+    vote_account_balance: u64,
+    rent_exempt_minimum: u64,
+    withdrawable_lamports: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CliSplitRecipient {
+    recipient: String,
+    basis_points: u16,
+}
+
+/// Vault reported by the `list` subcommand
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CliRewardsVaultListing {
+    vote_account: Option<String>,
+    vault_address: String,
+    version: u8,
+    original_withdraw_authority: String,
+    rewards_authority: String,
+}
+
+/// Offset of `authorized_withdrawer` in a vote account, following the version and node pubkey
+const VOTE_ACCOUNT_AUTHORIZED_WITHDRAWER_OFFSET: usize = 4 + 32;
+
+/// Fetches every vault account of every layout version, optionally only those of the given
+/// authorities
+async fn get_vaults(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+    original_withdraw_authority: Option<Pubkey>,
+    rewards_authority: Option<Pubkey>,
+) -> Result<Vec<(Pubkey, RewardsVaultState)>, String> {
+    let mut vaults = vec![];
+    for version in 0..=RewardsVaultState::VERSION {
+        let mut filters = vec![RpcFilterType::DataSize(
+            RewardsVaultState::size_of_version(version).unwrap() as u64,
+        )];
+        if let Some(original_withdraw_authority) = original_withdraw_authority {
+            filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                RewardsVaultState::original_withdraw_authority_offset(version),
+                original_withdraw_authority.as_ref(),
+            )));
+        }
+        if let Some(rewards_authority) = rewards_authority {
+            filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                RewardsVaultState::rewards_authority_offset(version),
+                rewards_authority.as_ref(),
+            )));
+        }
+
+        let accounts = rpc_client
+            .get_program_accounts_with_config(
+                program_id,
+                RpcProgramAccountsConfig {
+                    filters: Some(filters),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: Some(UiAccountEncoding::Base64),
+                        commitment: Some(rpc_client.commitment()),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    ..RpcProgramAccountsConfig::default()
+                },
+            )
+            .await
+            .map_err(|err| format!("error: unable to get vault accounts: {}", err))?;
+
+        vaults.extend(accounts.into_iter().filter_map(|(vault_address, account)| {
+            RewardsVaultState::unpack(&account.data)
+                .ok()
+                .map(|vault_state| (vault_address, vault_state))
+        }));
+    }
+    Ok(vaults)
+}
+
+/// Maps vault addresses back to their vote accounts. The vote accounts known to the cluster are
+/// tried first, the vote program is then searched for the withdraw authority of any remaining vault
+async fn get_vault_vote_accounts(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+    vault_addresses: &[Pubkey],
+) -> Result<HashMap<Pubkey, Pubkey>, String> {
+    let vote_accounts = rpc_client
+        .get_vote_accounts_with_config(RpcGetVoteAccountsConfig {
+            commitment: Some(rpc_client.commitment()),
+            keep_unstaked_delinquents: Some(true),
+            ..RpcGetVoteAccountsConfig::default()
+        })
+        .await
+        .map_err(|err| format!("error: unable to get vote accounts: {}", err))?;
+
+    let mut vault_vote_accounts = vote_accounts
+        .current
+        .iter()
+        .chain(vote_accounts.delinquent.iter())
+        .filter_map(|vote_account_info| Pubkey::from_str(&vote_account_info.vote_pubkey).ok())
+        .map(|vote_account| {
+            (
+                sol_rewards_vault_program::get_rewards_vault_address(program_id, &vote_account),
+                vote_account,
+            )
+        })
+        .filter(|(vault_address, _)| vault_addresses.contains(vault_address))
+        .collect::<HashMap<_, _>>();
+
+    for vault_address in vault_addresses {
+        if vault_vote_accounts.contains_key(vault_address) {
+            continue;
+        }
+        let vote_accounts = rpc_client
+            .get_program_accounts_with_config(
+                &vote::program::id(),
+                RpcProgramAccountsConfig {
+                    filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                        VOTE_ACCOUNT_AUTHORIZED_WITHDRAWER_OFFSET,
+                        vault_address.as_ref(),
+                    ))]),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: Some(UiAccountEncoding::Base64),
+                        commitment: Some(rpc_client.commitment()),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    ..RpcProgramAccountsConfig::default()
+                },
+            )
+            .await
+            .map_err(|err| format!("error: unable to get vote accounts: {}", err))?;
+
+        if let Some((vote_account, _)) = vote_accounts.into_iter().find(|(vote_account, _)| {
+            sol_rewards_vault_program::get_rewards_vault_address(program_id, vote_account)
+                == *vault_address
+        }) {
+            vault_vote_accounts.insert(*vault_address, vote_account);
+        }
+    }
+    Ok(vault_vote_accounts)
+}
+
+/// Fetches a confirmed transaction and decodes the rewards vault events in its log messages
+async fn get_events(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+    signature: &Signature,
+) -> Result<Vec<RewardsVaultEvent>, String> {
+    let transaction = rpc_client
+        .get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Json),
+                commitment: Some(rpc_client.commitment()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .await
+        .map_err(|err| format!("error: unable to get transaction {}: {}", signature, err))?;
+
+    let log_messages = transaction
+        .transaction
+        .meta
+        .and_then(|meta| Option::<Vec<String>>::from(meta.log_messages))
+        .ok_or_else(|| format!("error: no log messages for transaction {}", signature))?;
+
+    Ok(sol_rewards_vault_program::event::parse_logs(
+        program_id,
+        &log_messages,
+    ))
+}
+
+fn print_event(event: &RewardsVaultEvent) {
+    match event {
+        RewardsVaultEvent::Enter {
+            vote_account,
+            withdraw_authority,
+            rewards_authority,
+            reserve_lamports,
+            epoch,
+        } => println!(
+            "Epoch {}: {} entered its rewards vault, withdraw authority {}, rewards authority {}, \
+             reserve {}",
+            epoch,
+            vote_account,
+            withdraw_authority,
+            rewards_authority,
+            Sol(*reserve_lamports)
+        ),
+        RewardsVaultEvent::Leave {
+            vote_account,
+            withdraw_authority,
+            epoch,
+        } => println!(
+            "Epoch {}: {} left its rewards vault, withdraw authority {}",
+            epoch, vote_account, withdraw_authority
+        ),
+        RewardsVaultEvent::WithdrawRewards {
+            vote_account,
+            rewards_recipient,
+            lamports,
+            epoch,
+        } => println!(
+            "Epoch {}: {} withdrawn from {} to {}",
+            epoch,
+            Sol(*lamports),
+            vote_account,
+            rewards_recipient
+        ),
+        RewardsVaultEvent::SetRewardsAuthority {
+            vote_account,
+            rewards_authority,
+            epoch,
+        } => println!(
+            "Epoch {}: {} rewards authority set to {}",
+            epoch, vote_account, rewards_authority
+        ),
+        RewardsVaultEvent::ProposeWithdrawAuthority {
+            vote_account,
+            pending_withdraw_authority,
+            epoch,
+        } => println!(
+            "Epoch {}: {} withdraw authority {} proposed",
+            epoch, vote_account, pending_withdraw_authority
+        ),
+        RewardsVaultEvent::AcceptWithdrawAuthority {
+            vote_account,
+            withdraw_authority,
+            epoch,
+        } => println!(
+            "Epoch {}: {} withdraw authority {} accepted",
+            epoch, vote_account, withdraw_authority
+        ),
+        RewardsVaultEvent::CancelWithdrawAuthority {
+            vote_account,
+            epoch,
+        } => println!(
+            "Epoch {}: {} withdraw authority proposal cancelled",
+            epoch, vote_account
+        ),
+        RewardsVaultEvent::UpdateValidatorIdentity {
+            vote_account,
+            validator_identity,
+            epoch,
+        } => println!(
+            "Epoch {}: {} validator identity set to {}",
+            epoch, vote_account, validator_identity
+        ),
+        RewardsVaultEvent::AuthorizeVoter {
+            vote_account,
+            authorized_voter,
+            epoch,
+        } => println!(
+            "Epoch {}: {} authorized voter set to {}",
+            epoch, vote_account, authorized_voter
+        ),
+        RewardsVaultEvent::SetReserve {
+            vote_account,
+            reserve_lamports,
+            epoch,
+        } => println!(
+            "Epoch {}: {} reserve set to {}",
+            epoch,
+            vote_account,
+            Sol(*reserve_lamports)
+        ),
+        RewardsVaultEvent::SetRewardsRecipient {
+            vote_account,
+            rewards_recipient,
+            epoch,
+        } => {
+            if *rewards_recipient == Pubkey::default() {
+                println!(
+                    "Epoch {}: {} rewards recipient unlocked",
+                    epoch, vote_account
+                )
+            } else {
+                println!(
+                    "Epoch {}: {} rewards recipient locked to {}",
+                    epoch, vote_account, rewards_recipient
+                )
+            }
+        }
+        RewardsVaultEvent::SetCrankMode {
+            vote_account,
+            crank_enabled,
+            epoch,
+        } => println!(
+            "Epoch {}: {} crank {}",
+            epoch,
+            vote_account,
+            if *crank_enabled {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        ),
+        RewardsVaultEvent::SetWithdrawLimit {
+            vote_account,
+            max_withdraw_per_epoch,
+            epoch,
+        } => {
+            if *max_withdraw_per_epoch == 0 {
+                println!("Epoch {}: {} withdraw limit removed", epoch, vote_account)
+            } else {
+                println!(
+                    "Epoch {}: {} withdraw limit set to {} per epoch",
+                    epoch,
+                    vote_account,
+                    Sol(*max_withdraw_per_epoch)
+                )
+            }
+        }
+        RewardsVaultEvent::SetSplit {
+            vote_account,
+            split_recipients,
+            split_bps,
+            epoch,
+        } => {
+            let split = split_recipients
+                .iter()
+                .zip(split_bps)
+                .filter(|(_, bps)| **bps > 0)
+                .map(|(split_recipient, bps)| format!("{} ({} bps)", split_recipient, bps))
+                .collect::<Vec<_>>();
+            if split.is_empty() {
+                println!("Epoch {}: {} rewards split cleared", epoch, vote_account)
+            } else {
+                println!(
+                    "Epoch {}: {} rewards split set to {}",
+                    epoch,
+                    vote_account,
+                    split.join(", ")
+                )
+            }
+        }
+        RewardsVaultEvent::UpdateCommission {
+            vote_account,
+            commission,
+            epoch,
+        } => println!(
+            "Epoch {}: {} commission set to {}%",
+            epoch, vote_account, commission
+        ),
+        RewardsVaultEvent::Migrate {
+            vote_account,
+            previous_version,
+            version,
+            epoch,
+        } => println!(
+            "Epoch {}: {} vault migrated from version {} to version {}",
+            epoch, vote_account, previous_version, version
+        ),
+    }
+}
+
+fn print_rewards_vault(vault: &CliRewardsVault) {
+    println!("Vault address: {}", vault.vault_address);
+    if vault.version == RewardsVaultState::VERSION {
+        println!("Version: {}", vault.version);
+    } else {
+        println!(
+            "Version: {} (run `migrate` to upgrade to version {})",
+            vault.version,
+            RewardsVaultState::VERSION
+        );
+    }
+    println!(
+        "Original withdraw authority: {}",
+        vault.original_withdraw_authority
+    );
+    if let Some(pending_withdraw_authority) = &vault.pending_withdraw_authority {
+        println!("Pending withdraw authority: {}", pending_withdraw_authority);
+    }
+    println!("Rewards authority: {}", vault.rewards_authority);
+    println!("Reserve: {}", Sol(vault.reserve_lamports));
+    match &vault.locked_rewards_recipient {
+        Some(locked_rewards_recipient) => {
+            println!("Rewards recipient: {} (locked)", locked_rewards_recipient)
+        }
+        None => println!("Rewards recipient: unlocked"),
+    }
+    println!(
+        "Crank: {}",
+        if vault.crank_enabled {
+            "enabled"
+        } else {
+            "disabled"
+        }
+    );
+    match vault.max_withdraw_per_epoch {
+        Some(max_withdraw_per_epoch) => println!(
+            "Withdraw limit: {} per epoch ({} withdrawn in epoch {})",
+            Sol(max_withdraw_per_epoch),
+            Sol(vault.withdrawn_this_epoch),
+            vault.last_withdraw_epoch
+        ),
+        None => println!("Withdraw limit: none"),
+    }
+    if vault.split.is_empty() {
+        println!("Rewards split: none");
+    } else {
+        println!("Rewards split:");
+        for (i, split_recipient) in vault.split.iter().enumerate() {
+            println!(
+                "  {}: {} basis points{}",
+                split_recipient.recipient,
+                split_recipient.basis_points,
+                if i == 0 { " (primary)" } else { "" }
+            );
+        }
+    }
+    match (vault.total_rewards_withdrawn, vault.withdraw_count) {
+        (Some(total_rewards_withdrawn), Some(withdraw_count)) => {
+            match (vault.entered_epoch, vault.entered_slot) {
+                (Some(entered_epoch), Some(entered_slot)) => {
+                    println!("Entered: epoch {}, slot {}", entered_epoch, entered_slot)
+                }
+                _ => println!("Entered: unknown (migrated vault)"),
+            }
+            println!(
+                "Total rewards withdrawn: {} in {} withdrawal{}",
+                Sol(total_rewards_withdrawn),
+                withdraw_count,
+                if withdraw_count == 1 { "" } else { "s" }
+            );
+            if withdraw_count > 0 {
+                println!("Last withdraw epoch: {}", vault.last_withdraw_epoch);
+            }
+        }
+        _ => println!("Total rewards withdrawn: not recorded (run `migrate` to start recording)"),
+    }
+
+    println!();
+    println!("Vote account: {}", vault.vote_account);
+    if vault.vote_account_in_vault {
+        println!(
+            "Withdraw authority: {} (vault)",
+            vault.vote_account_withdrawer
+        );
+    } else {
+        println!(
+            "Withdraw authority: {} (WARNING: not the vault, rewards are not protected)",
+            vault.vote_account_withdrawer
+        );
+    }
+    println!("Balance: {}", Sol(vault.vote_account_balance));
+    println!("Rent-exempt minimum: {}", Sol(vault.rent_exempt_minimum));
+    println!("Withdrawable: {}", Sol(vault.withdrawable_lamports));
+}
+
+fn parse_split_recipient(s: &str) -> Result<(Pubkey, u16), String> {
+    let (split_recipient, bps) = s
+        .split_once(':')
+        .ok_or_else(|| format!("expected ADDRESS:BASIS_POINTS, got {}", s))?;
+    let split_recipient = Pubkey::from_str(split_recipient)
+        .map_err(|err| format!("invalid address {}: {}", split_recipient, err))?;
+    let bps = bps
+        .parse::<u16>()
+        .map_err(|err| format!("invalid basis points {}: {}", bps, err))?;
+    Ok((split_recipient, bps))
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let default_program_id = sol_rewards_vault_program::id().to_string();
     let app_matches = Command::new(crate_name!())
         .about(crate_description!())
         .version(crate_version!())
@@ -71,6 +852,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .global(true)
                 .help("Specify the fee-payer account"),
         )
+        .arg(
+            Arg::new("program_id")
+                .long("program-id")
+                .value_name("ADDRESS")
+                .validator(|s| is_valid_pubkey(s))
+                .takes_value(true)
+                .global(true)
+                .default_value(&default_program_id)
+                .help("Address of the rewards vault program deployment to use"),
+        )
         .arg(
             Arg::new("verbose")
                 .long("verbose")
@@ -92,6 +883,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .subcommand(
             Command::new("enter")
                 .about("Place a vote account in its rewards vault")
+                .offline_args()
+                .nonce_args(false)
+                .arg(dump_message_arg())
                 .arg(
                     Arg::new("vote_account")
                         .validator(|s| is_valid_pubkey(s))
@@ -116,10 +910,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         .required(true)
                         .help("Authority to withdraw rewards while vote account resides in its rewards vault"),
                 )
+                .arg(
+                    Arg::new("reserve")
+                        .long("reserve")
+                        .validator(|s| is_amount(s))
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .help("Amount to retain in the vote account that the rewards authority may never withdraw, in SOL [default: 0]"),
+                )
         )
         .subcommand(
             Command::new("leave")
                 .about("Remove a vote account from its rewards vault")
+                .offline_args()
+                .nonce_args(false)
+                .arg(dump_message_arg())
                 .arg(
                     Arg::new("vote_account")
                         .validator(|s| is_valid_pubkey(s))
@@ -140,6 +945,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .subcommand(
             Command::new("withdraw")
                 .about("Claim epoch rewards earned by a vote account residing in its rewards vault")
+                .offline_args()
+                .nonce_args(false)
+                .arg(dump_message_arg())
                 .arg(
                     Arg::new("vote_account")
                         .validator(|s| is_valid_pubkey(s))
@@ -161,138 +969,1325 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         .validator(|s| is_valid_pubkey(s))
                         .value_name("REWARDS_RECIPIENT_ADDRESS")
                         .takes_value(true)
-                        .help("Account to credit the epoch rewards to [default: Rewards authority]"),
+                        .help("Account to credit the epoch rewards to, or the stake withdraw authority with --stake \
+                               [default: Locked rewards recipient, otherwise Rewards authority]"),
                 )
-
-        )
-        .get_matches();
-
-    let (command, matches) = app_matches.subcommand().unwrap();
-    let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
-
-    let cli_config = if let Some(config_file) = matches.value_of("config_file") {
-        solana_cli_config::Config::load(config_file).unwrap_or_default()
-    } else {
-        solana_cli_config::Config::default()
-    };
-
-    let fee_payer = DefaultSigner::new(
-        "fee_payer",
-        matches
-            .value_of(&"fee_payer")
-            .map(|s| s.to_string())
-            .unwrap_or_else(|| cli_config.keypair_path.clone()),
-    );
-
-    let json_rpc_url = normalize_to_url_if_moniker(
-        matches
-            .value_of("json_rpc_url")
-            .unwrap_or(&cli_config.json_rpc_url),
-    );
-
-    let fee_payer = fee_payer
-        .signer_from_path(matches, &mut wallet_manager)
-        .unwrap_or_else(|err| {
-            eprintln!("error: {}", err);
-            exit(1);
-        });
-    let verbose = matches.is_present("verbose");
-
-    solana_logger::setup_with_default("solana=info");
-
-    if verbose {
-        println!("JSON RPC URL: {}", json_rpc_url);
-    }
-    let rpc_client = RpcClient::new_with_commitment(json_rpc_url, CommitmentConfig::confirmed());
-
-    match (command, matches) {
-        ("enter", arg_matches) => {
-            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
-            let (withdraw_authority_signer, withdraw_authority) = {
-                let (withdraw_authority_signer, withdraw_authority) = signer_of(
-                    arg_matches,
-                    "withdraw_authority",
-                    &mut wallet_manager,
-                )?;
-                (
-                    withdraw_authority_signer.expect("withdraw_authority_signer"),
-                    withdraw_authority.expect("withdraw_authority"),
+                .arg(
+                    Arg::new("amount")
+                        .long("amount")
+                        .validator(|s| is_amount(s))
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .help("Amount of epoch rewards to withdraw, in SOL [default: all withdrawable rewards]"),
                 )
-            };
-            let rewards_authority = pubkey_of(arg_matches, "rewards_authority").unwrap();
-
-            send_message(
-                &rpc_client,
-                Message::new(
-                    &[sol_rewards_vault_program::instruction::enter(
-                        vote_account,
-                        fee_payer.pubkey(),
-                        withdraw_authority,
-                        rewards_authority,
-                    )],
-                    Some(&fee_payer.pubkey()),
-                ),
-                &vec![fee_payer, withdraw_authority_signer],
-            )
-            .await?;
-        }
-        ("leave", arg_matches) => {
-            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
-            let (withdraw_authority_signer, withdraw_authority) = {
-                let (withdraw_authority_signer, withdraw_authority) = signer_of(
-                    arg_matches,
-                    "withdraw_authority",
-                    &mut wallet_manager,
-                )?;
-                (
-                    withdraw_authority_signer.expect("withdraw_authority_signer"),
-                    withdraw_authority.expect("withdraw_authority"),
+                .arg(
+                    Arg::new("split")
+                        .long("split")
+                        .takes_value(false)
+                        .conflicts_with_all(&["rewards_recipient", "amount"])
+                        .help("Split all withdrawable rewards across the configured split recipients"),
                 )
-            };
-
-            send_message(
-                &rpc_client,
-                Message::new(
-                    &[sol_rewards_vault_program::instruction::leave(
-                        vote_account,
-                        fee_payer.pubkey(),
-                        withdraw_authority,
-                    )],
-                    Some(&fee_payer.pubkey()),
-                ),
-                &vec![fee_payer, withdraw_authority_signer],
-            )
-            .await?;
-        }
-        ("withdraw", arg_matches) => {
-            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
-            let (rewards_authority_signer, rewards_authority) = {
-                let (rewards_authority_signer, rewards_authority) = signer_of(
-                    arg_matches,
-                    "rewards_authority",
-                    &mut wallet_manager,
-                )?;
-                (
-                    rewards_authority_signer.expect("rewards_authority_signer"),
-                    rewards_authority.expect("rewards_authority"),
+                .arg(
+                    Arg::new("stake_account")
+                        .long("stake")
+                        .value_name("STAKE_ACCOUNT_KEYPAIR")
+                        .validator(|s| is_valid_signer(s))
+                        .takes_value(true)
+                        .conflicts_with("split")
+                        .help("Stake the epoch rewards in this new stake account, delegated to the vote account"),
                 )
-            };
-            let rewards_recipient =
-                pubkey_of(arg_matches, "rewards_recipient").unwrap_or(rewards_authority);
 
-            send_message(
-                &rpc_client,
-                Message::new(
-                    &[sol_rewards_vault_program::instruction::withdraw_rewards(
-                        vote_account,
-                        rewards_recipient,
-                        rewards_authority,
-                    )],
-                    Some(&fee_payer.pubkey()),
-                ),
-                &vec![fee_payer, rewards_authority_signer],
-            )
-            .await?;
+        )
+        .subcommand(
+            Command::new("set-rewards-authority")
+                .about("Install a new rewards authority for a vote account residing in its rewards vault")
+                .offline_args()
+                .nonce_args(false)
+                .arg(dump_message_arg())
+                .arg(
+                    Arg::new("vote_account")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("VOTE_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account address"),
+                )
+                .arg(
+                    Arg::new("withdraw_authority")
+                        .value_name("WITHDRAW_AUTHORITY_KEYPAIR")
+                        .validator(|s| is_valid_signer(s))
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account withdraw authority"),
+                )
+                .arg(
+                    Arg::new("new_rewards_authority")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("NEW_REWARDS_AUTHORITY_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .help("New authority to withdraw rewards while vote account resides in its rewards vault"),
+                )
+        )
+        .subcommand(
+            Command::new("set-reserve")
+                .about("Set the amount retained in a vote account residing in its rewards vault")
+                .offline_args()
+                .nonce_args(false)
+                .arg(dump_message_arg())
+                .arg(
+                    Arg::new("vote_account")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("VOTE_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account address"),
+                )
+                .arg(
+                    Arg::new("withdraw_authority")
+                        .value_name("WITHDRAW_AUTHORITY_KEYPAIR")
+                        .validator(|s| is_valid_signer(s))
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account withdraw authority"),
+                )
+                .arg(
+                    Arg::new("reserve")
+                        .validator(|s| is_amount(s))
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Amount to retain in the vote account that the rewards authority may never withdraw, in SOL"),
+                )
+        )
+        .subcommand(
+            Command::new("set-withdraw-limit")
+                .about("Limit the amount of epoch rewards withdrawn per epoch from a vote account residing in its rewards vault")
+                .offline_args()
+                .nonce_args(false)
+                .arg(dump_message_arg())
+                .arg(
+                    Arg::new("vote_account")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("VOTE_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account address"),
+                )
+                .arg(
+                    Arg::new("withdraw_authority")
+                        .value_name("WITHDRAW_AUTHORITY_KEYPAIR")
+                        .validator(|s| is_valid_signer(s))
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account withdraw authority"),
+                )
+                .arg(
+                    Arg::new("withdraw_limit")
+                        .validator(|s| is_amount(s))
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Maximum amount that may be withdrawn per epoch, in SOL. Use 0 for no limit"),
+                )
+        )
+        .subcommand(
+            Command::new("set-split")
+                .about("Configure the recipients epoch rewards are split across for a vote account residing in its rewards vault")
+                .offline_args()
+                .nonce_args(false)
+                .arg(dump_message_arg())
+                .arg(
+                    Arg::new("vote_account")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("VOTE_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account address"),
+                )
+                .arg(
+                    Arg::new("withdraw_authority")
+                        .value_name("WITHDRAW_AUTHORITY_KEYPAIR")
+                        .validator(|s| is_valid_signer(s))
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account withdraw authority"),
+                )
+                .arg(
+                    Arg::new("split")
+                        .validator(|s| parse_split_recipient(s).map(|_| ()))
+                        .value_name("ADDRESS:BASIS_POINTS")
+                        .takes_value(true)
+                        .multiple_values(true)
+                        .max_values(MAX_SPLIT_RECIPIENTS)
+                        .required_unless_present("clear")
+                        .help("Split recipient and its share of epoch rewards in basis points, totalling 10000. \
+                               The first recipient is the primary recipient and also receives any rounding dust"),
+                )
+                .arg(
+                    Arg::new("clear")
+                        .long("clear")
+                        .takes_value(false)
+                        .conflicts_with("split")
+                        .help("Clear the split"),
+                )
+        )
+        .subcommand(
+            Command::new("set-rewards-recipient")
+                .about("Lock the account that epoch rewards may be withdrawn to for a vote account residing in its rewards vault")
+                .offline_args()
+                .nonce_args(false)
+                .arg(dump_message_arg())
+                .arg(
+                    Arg::new("vote_account")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("VOTE_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account address"),
+                )
+                .arg(
+                    Arg::new("withdraw_authority")
+                        .value_name("WITHDRAW_AUTHORITY_KEYPAIR")
+                        .validator(|s| is_valid_signer(s))
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account withdraw authority"),
+                )
+                .arg(
+                    Arg::new("rewards_recipient")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("REWARDS_RECIPIENT_ADDRESS")
+                        .takes_value(true)
+                        .required_unless_present("unlock")
+                        .help("The only account epoch rewards may be withdrawn to"),
+                )
+                .arg(
+                    Arg::new("unlock")
+                        .long("unlock")
+                        .takes_value(false)
+                        .conflicts_with("rewards_recipient")
+                        .help("Allow the rewards authority to withdraw epoch rewards to any account"),
+                )
+        )
+        .subcommand(
+            Command::new("set-crank-mode")
+                .about("Allow anybody to withdraw epoch rewards to the locked rewards recipient of a vote account residing in its rewards vault")
+                .offline_args()
+                .nonce_args(false)
+                .arg(dump_message_arg())
+                .arg(
+                    Arg::new("vote_account")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("VOTE_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account address"),
+                )
+                .arg(
+                    Arg::new("withdraw_authority")
+                        .value_name("WITHDRAW_AUTHORITY_KEYPAIR")
+                        .validator(|s| is_valid_signer(s))
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account withdraw authority"),
+                )
+                .arg(
+                    Arg::new("crank_mode")
+                        .value_name("MODE")
+                        .possible_values(["on", "off"])
+                        .takes_value(true)
+                        .required(true)
+                        .help("Whether anybody may withdraw epoch rewards to the locked rewards recipient"),
+                )
+        )
+        .subcommand(
+            Command::new("crank")
+                .about("Withdraw epoch rewards earned by a vote account residing in its rewards vault to its locked rewards recipient")
+                .offline_args()
+                .nonce_args(false)
+                .arg(dump_message_arg())
+                .arg(
+                    Arg::new("vote_account")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("VOTE_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account address"),
+                )
+                .arg(
+                    Arg::new("rewards_recipient")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("REWARDS_RECIPIENT_ADDRESS")
+                        .takes_value(true)
+                        .help("Locked rewards recipient, required with --sign-only or --dump-message \
+                               [default: Locked rewards recipient read from the vault]"),
+                )
+        )
+        .subcommand(
+            Command::new("update-commission")
+                .about("Update the commission of a vote account residing in its rewards vault")
+                .offline_args()
+                .nonce_args(false)
+                .arg(dump_message_arg())
+                .arg(
+                    Arg::new("vote_account")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("VOTE_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account address"),
+                )
+                .arg(
+                    Arg::new("withdraw_authority")
+                        .value_name("WITHDRAW_AUTHORITY_KEYPAIR")
+                        .validator(|s| is_valid_signer(s))
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account withdraw authority"),
+                )
+                .arg(
+                    Arg::new("commission")
+                        .validator(|s| is_valid_percentage(s))
+                        .value_name("PERCENTAGE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The new commission"),
+                )
+        )
+        .subcommand(
+            Command::new("update-identity")
+                .about("Update the validator identity of a vote account residing in its rewards vault")
+                .offline_args()
+                .nonce_args(false)
+                .arg(dump_message_arg())
+                .arg(
+                    Arg::new("vote_account")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("VOTE_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account address"),
+                )
+                .arg(
+                    Arg::new("withdraw_authority")
+                        .value_name("WITHDRAW_AUTHORITY_KEYPAIR")
+                        .validator(|s| is_valid_signer(s))
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account withdraw authority"),
+                )
+                .arg(
+                    Arg::new("new_identity")
+                        .value_name("NEW_IDENTITY_KEYPAIR")
+                        .validator(|s| is_valid_signer(s))
+                        .takes_value(true)
+                        .required(true)
+                        .help("New validator identity"),
+                )
+        )
+        .subcommand(
+            Command::new("authorize-voter")
+                .about("Authorize a new voter for a vote account residing in its rewards vault")
+                .offline_args()
+                .nonce_args(false)
+                .arg(dump_message_arg())
+                .arg(
+                    Arg::new("vote_account")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("VOTE_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account address"),
+                )
+                .arg(
+                    Arg::new("withdraw_authority")
+                        .value_name("WITHDRAW_AUTHORITY_KEYPAIR")
+                        .validator(|s| is_valid_signer(s))
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account withdraw authority"),
+                )
+                .arg(
+                    Arg::new("new_authorized_voter")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("NEW_AUTHORIZED_VOTER_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .help("New authorized voter, effective after the current leader schedule epoch"),
+                )
+        )
+        .subcommand(
+            Command::new("propose-withdraw-authority")
+                .about("Nominate a new withdraw authority for a vote account residing in its rewards vault")
+                .offline_args()
+                .nonce_args(false)
+                .arg(dump_message_arg())
+                .arg(
+                    Arg::new("vote_account")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("VOTE_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account address"),
+                )
+                .arg(
+                    Arg::new("withdraw_authority")
+                        .value_name("WITHDRAW_AUTHORITY_KEYPAIR")
+                        .validator(|s| is_valid_signer(s))
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account withdraw authority"),
+                )
+                .arg(
+                    Arg::new("new_withdraw_authority")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("NEW_WITHDRAW_AUTHORITY_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Nominated withdraw authority"),
+                )
+        )
+        .subcommand(
+            Command::new("accept-withdraw-authority")
+                .about("Accept a withdraw authority nomination for a vote account residing in its rewards vault")
+                .offline_args()
+                .nonce_args(false)
+                .arg(dump_message_arg())
+                .arg(
+                    Arg::new("vote_account")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("VOTE_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account address"),
+                )
+                .arg(
+                    Arg::new("new_withdraw_authority")
+                        .value_name("NEW_WITHDRAW_AUTHORITY_KEYPAIR")
+                        .validator(|s| is_valid_signer(s))
+                        .takes_value(true)
+                        .required(true)
+                        .help("Nominated withdraw authority"),
+                )
+        )
+        .subcommand(
+            Command::new("cancel-withdraw-authority")
+                .about("Cancel a pending withdraw authority nomination for a vote account residing in its rewards vault")
+                .offline_args()
+                .nonce_args(false)
+                .arg(dump_message_arg())
+                .arg(
+                    Arg::new("vote_account")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("VOTE_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account address"),
+                )
+                .arg(
+                    Arg::new("withdraw_authority")
+                        .value_name("WITHDRAW_AUTHORITY_KEYPAIR")
+                        .validator(|s| is_valid_signer(s))
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account withdraw authority"),
+                )
+        )
+        .subcommand(
+            Command::new("migrate")
+                .about("Migrate the rewards vault of a vote account to the current account layout")
+                .offline_args()
+                .nonce_args(false)
+                .arg(dump_message_arg())
+                .arg(
+                    Arg::new("vote_account")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("VOTE_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account address"),
+                )
+        )
+        .subcommand(
+            Command::new("events")
+                .about("Display the rewards vault events logged by a transaction")
+                .arg(
+                    Arg::new("signature")
+                        .validator(|s| Signature::from_str(s).map(|_| ()).map_err(|err| err.to_string()))
+                        .value_name("SIGNATURE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Transaction signature"),
+                )
+        )
+        .subcommand(
+            Command::new("list")
+                .about("List the rewards vaults of the program")
+                .arg(
+                    Arg::new("withdraw_authority")
+                        .long("withdraw-authority")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("ADDRESS")
+                        .takes_value(true)
+                        .help("Only list vaults of this original withdraw authority"),
+                )
+                .arg(
+                    Arg::new("rewards_authority")
+                        .long("rewards-authority")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("ADDRESS")
+                        .takes_value(true)
+                        .help("Only list vaults of this rewards authority"),
+                )
+                .arg(
+                    Arg::new("output_format")
+                        .long("output")
+                        .value_name("FORMAT")
+                        .takes_value(true)
+                        .possible_values(["json", "json-compact"])
+                        .help("Return information in specified output format"),
+                )
+        )
+        .subcommand(
+            Command::new("show")
+                .about("Display the rewards vault of a vote account and its withdrawable rewards")
+                .arg(
+                    Arg::new("vote_account")
+                        .validator(|s| is_valid_pubkey(s))
+                        .value_name("VOTE_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account address"),
+                )
+                .arg(
+                    Arg::new("output_format")
+                        .long("output")
+                        .value_name("FORMAT")
+                        .takes_value(true)
+                        .possible_values(["json", "json-compact"])
+                        .help("Return information in specified output format"),
+                )
+        )
+        .get_matches();
+
+    let (command, matches) = app_matches.subcommand().unwrap();
+    let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+
+    let cli_config = if let Some(config_file) = matches.value_of("config_file") {
+        solana_cli_config::Config::load(config_file).unwrap_or_default()
+    } else {
+        solana_cli_config::Config::default()
+    };
+
+    let fee_payer = DefaultSigner::new(
+        "fee_payer",
+        matches
+            .value_of(&"fee_payer")
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| cli_config.keypair_path.clone()),
+    );
+
+    let json_rpc_url = normalize_to_url_if_moniker(
+        matches
+            .value_of("json_rpc_url")
+            .unwrap_or(&cli_config.json_rpc_url),
+    );
+
+    let fee_payer = fee_payer
+        .signer_from_path_with_config(
+            matches,
+            &mut wallet_manager,
+            &SignerFromPathConfig {
+                allow_null_signer: is_dump_message(matches),
+            },
+        )
+        .unwrap_or_else(|err| {
+            eprintln!("error: {}", err);
+            exit(1);
+        });
+    let program_id = pubkey_of(matches, "program_id").unwrap();
+    let verbose = matches.is_present("verbose");
+
+    solana_logger::setup_with_default("solana=info");
+
+    if verbose {
+        println!("JSON RPC URL: {}", json_rpc_url);
+        println!("Program Id: {}", program_id);
+    }
+    let rpc_client = RpcClient::new_with_commitment(json_rpc_url, CommitmentConfig::confirmed());
+
+    match (command, matches) {
+        ("enter", arg_matches) => {
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+            let (withdraw_authority_signer, withdraw_authority) = {
+                let (withdraw_authority_signer, withdraw_authority) =
+                    signer_or_address_of(arg_matches, "withdraw_authority", &mut wallet_manager)?;
+                (
+                    withdraw_authority_signer.expect("withdraw_authority_signer"),
+                    withdraw_authority.expect("withdraw_authority"),
+                )
+            };
+            let rewards_authority = pubkey_of(arg_matches, "rewards_authority").unwrap();
+            let reserve = lamports_of_sol(arg_matches, "reserve").unwrap_or(0);
+
+            send_message(
+                &rpc_client,
+                &program_id,
+                arg_matches,
+                &mut wallet_manager,
+                &[sol_rewards_vault_program::instruction::enter_with_reserve(
+                    program_id,
+                    vote_account,
+                    fee_payer.pubkey(),
+                    withdraw_authority,
+                    rewards_authority,
+                    reserve,
+                )],
+                vec![fee_payer, withdraw_authority_signer],
+            )
+            .await?;
+        }
+        ("leave", arg_matches) => {
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+            let (withdraw_authority_signer, withdraw_authority) = {
+                let (withdraw_authority_signer, withdraw_authority) =
+                    signer_or_address_of(arg_matches, "withdraw_authority", &mut wallet_manager)?;
+                (
+                    withdraw_authority_signer.expect("withdraw_authority_signer"),
+                    withdraw_authority.expect("withdraw_authority"),
+                )
+            };
+
+            send_message(
+                &rpc_client,
+                &program_id,
+                arg_matches,
+                &mut wallet_manager,
+                &[sol_rewards_vault_program::instruction::leave(
+                    program_id,
+                    vote_account,
+                    fee_payer.pubkey(),
+                    withdraw_authority,
+                )],
+                vec![fee_payer, withdraw_authority_signer],
+            )
+            .await?;
+        }
+        ("withdraw", arg_matches) => {
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+            let (rewards_authority_signer, rewards_authority) = {
+                let (rewards_authority_signer, rewards_authority) =
+                    signer_or_address_of(arg_matches, "rewards_authority", &mut wallet_manager)?;
+                (
+                    rewards_authority_signer.expect("rewards_authority_signer"),
+                    rewards_authority.expect("rewards_authority"),
+                )
+            };
+            let amount = lamports_of_sol(arg_matches, "amount");
+
+            let vault_state = if is_offline(arg_matches) {
+                None
+            } else {
+                let vault_state = get_vault_state(&rpc_client, &program_id, &vote_account).await?;
+                let withdrawable_lamports =
+                    get_withdrawable_lamports(&rpc_client, &vote_account, &vault_state).await?;
+                if amount.unwrap_or(withdrawable_lamports) == 0 {
+                    println!("Nothing to withdraw");
+                    return Ok(());
+                }
+                if let Some(amount) = amount {
+                    if amount > withdrawable_lamports {
+                        return Err(format!(
+                            "error: only {} is withdrawable",
+                            Sol(withdrawable_lamports)
+                        )
+                        .into());
+                    }
+                }
+                Some(vault_state)
+            };
+
+            if arg_matches.is_present("split") {
+                let vault_state = vault_state.ok_or(
+                    "error: --split reads the split recipients from the vault and cannot be \
+                     combined with --sign-only or --dump-message",
+                )?;
+                let split_recipients = vault_state
+                    .split()
+                    .into_iter()
+                    .map(|(split_recipient, _)| split_recipient)
+                    .collect::<Vec<_>>();
+                if split_recipients.is_empty() {
+                    return Err(format!("error: no split configured for {}", vote_account).into());
+                }
+
+                send_message(
+                    &rpc_client,
+                    &program_id,
+                    arg_matches,
+                    &mut wallet_manager,
+                    &[
+                        sol_rewards_vault_program::instruction::withdraw_rewards_split(
+                            program_id,
+                            vote_account,
+                            rewards_authority,
+                            &split_recipients,
+                        ),
+                    ],
+                    vec![fee_payer, rewards_authority_signer],
+                )
+                .await?;
+                return Ok(());
+            }
+
+            let rewards_recipient = pubkey_of(arg_matches, "rewards_recipient");
+            let rewards_recipient = match (rewards_recipient, vault_state) {
+                (Some(rewards_recipient), _) => rewards_recipient,
+                (None, Some(vault_state)) => {
+                    if vault_state.locked_rewards_recipient == Pubkey::default() {
+                        rewards_authority
+                    } else {
+                        vault_state.locked_rewards_recipient
+                    }
+                }
+                (None, None) => {
+                    return Err(
+                        "error: REWARDS_RECIPIENT_ADDRESS is required with --sign-only or \
+                         --dump-message"
+                            .into(),
+                    )
+                }
+            };
+
+            if arg_matches.is_present("stake_account") {
+                let (stake_account_signer, stake_account) = {
+                    let (stake_account_signer, stake_account) =
+                        signer_or_address_of(arg_matches, "stake_account", &mut wallet_manager)?;
+                    (
+                        stake_account_signer.expect("stake_account_signer"),
+                        stake_account.expect("stake_account"),
+                    )
+                };
+
+                let instruction = match amount {
+                    Some(amount) => {
+                        sol_rewards_vault_program::instruction::withdraw_and_stake_with_amount(
+                            program_id,
+                            vote_account,
+                            stake_account,
+                            rewards_authority,
+                            rewards_recipient,
+                            amount,
+                        )
+                    }
+                    None => sol_rewards_vault_program::instruction::withdraw_and_stake(
+                        program_id,
+                        vote_account,
+                        stake_account,
+                        rewards_authority,
+                        rewards_recipient,
+                    ),
+                };
+
+                send_message(
+                    &rpc_client,
+                    &program_id,
+                    arg_matches,
+                    &mut wallet_manager,
+                    &[instruction],
+                    vec![fee_payer, rewards_authority_signer, stake_account_signer],
+                )
+                .await?;
+                return Ok(());
+            }
+
+            let instruction = match amount {
+                Some(amount) => {
+                    sol_rewards_vault_program::instruction::withdraw_rewards_with_amount(
+                        program_id,
+                        vote_account,
+                        rewards_recipient,
+                        rewards_authority,
+                        amount,
+                    )
+                }
+                None => sol_rewards_vault_program::instruction::withdraw_rewards(
+                    program_id,
+                    vote_account,
+                    rewards_recipient,
+                    rewards_authority,
+                ),
+            };
+
+            send_message(
+                &rpc_client,
+                &program_id,
+                arg_matches,
+                &mut wallet_manager,
+                &[instruction],
+                vec![fee_payer, rewards_authority_signer],
+            )
+            .await?;
+        }
+        ("set-rewards-authority", arg_matches) => {
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+            let (withdraw_authority_signer, withdraw_authority) = {
+                let (withdraw_authority_signer, withdraw_authority) =
+                    signer_or_address_of(arg_matches, "withdraw_authority", &mut wallet_manager)?;
+                (
+                    withdraw_authority_signer.expect("withdraw_authority_signer"),
+                    withdraw_authority.expect("withdraw_authority"),
+                )
+            };
+            let new_rewards_authority = pubkey_of(arg_matches, "new_rewards_authority").unwrap();
+
+            send_message(
+                &rpc_client,
+                &program_id,
+                arg_matches,
+                &mut wallet_manager,
+                &[
+                    sol_rewards_vault_program::instruction::set_rewards_authority(
+                        program_id,
+                        vote_account,
+                        withdraw_authority,
+                        new_rewards_authority,
+                    ),
+                ],
+                vec![fee_payer, withdraw_authority_signer],
+            )
+            .await?;
+        }
+        ("set-reserve", arg_matches) => {
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+            let (withdraw_authority_signer, withdraw_authority) = {
+                let (withdraw_authority_signer, withdraw_authority) =
+                    signer_or_address_of(arg_matches, "withdraw_authority", &mut wallet_manager)?;
+                (
+                    withdraw_authority_signer.expect("withdraw_authority_signer"),
+                    withdraw_authority.expect("withdraw_authority"),
+                )
+            };
+            let reserve = lamports_of_sol(arg_matches, "reserve").unwrap();
+
+            send_message(
+                &rpc_client,
+                &program_id,
+                arg_matches,
+                &mut wallet_manager,
+                &[sol_rewards_vault_program::instruction::set_reserve(
+                    program_id,
+                    vote_account,
+                    withdraw_authority,
+                    reserve,
+                )],
+                vec![fee_payer, withdraw_authority_signer],
+            )
+            .await?;
+        }
+        ("set-withdraw-limit", arg_matches) => {
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+            let (withdraw_authority_signer, withdraw_authority) = {
+                let (withdraw_authority_signer, withdraw_authority) =
+                    signer_or_address_of(arg_matches, "withdraw_authority", &mut wallet_manager)?;
+                (
+                    withdraw_authority_signer.expect("withdraw_authority_signer"),
+                    withdraw_authority.expect("withdraw_authority"),
+                )
+            };
+            let withdraw_limit = lamports_of_sol(arg_matches, "withdraw_limit").unwrap();
+
+            send_message(
+                &rpc_client,
+                &program_id,
+                arg_matches,
+                &mut wallet_manager,
+                &[sol_rewards_vault_program::instruction::set_withdraw_limit(
+                    program_id,
+                    vote_account,
+                    withdraw_authority,
+                    withdraw_limit,
+                )],
+                vec![fee_payer, withdraw_authority_signer],
+            )
+            .await?;
+        }
+        ("set-split", arg_matches) => {
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+            let (withdraw_authority_signer, withdraw_authority) = {
+                let (withdraw_authority_signer, withdraw_authority) =
+                    signer_or_address_of(arg_matches, "withdraw_authority", &mut wallet_manager)?;
+                (
+                    withdraw_authority_signer.expect("withdraw_authority_signer"),
+                    withdraw_authority.expect("withdraw_authority"),
+                )
+            };
+            let split = arg_matches
+                .values_of("split")
+                .map(|values| {
+                    values
+                        .map(|value| parse_split_recipient(value).unwrap())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+
+            send_message(
+                &rpc_client,
+                &program_id,
+                arg_matches,
+                &mut wallet_manager,
+                &[sol_rewards_vault_program::instruction::set_split(
+                    program_id,
+                    vote_account,
+                    withdraw_authority,
+                    &split,
+                )],
+                vec![fee_payer, withdraw_authority_signer],
+            )
+            .await?;
+        }
+        ("set-rewards-recipient", arg_matches) => {
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+            let (withdraw_authority_signer, withdraw_authority) = {
+                let (withdraw_authority_signer, withdraw_authority) =
+                    signer_or_address_of(arg_matches, "withdraw_authority", &mut wallet_manager)?;
+                (
+                    withdraw_authority_signer.expect("withdraw_authority_signer"),
+                    withdraw_authority.expect("withdraw_authority"),
+                )
+            };
+            let rewards_recipient = pubkey_of(arg_matches, "rewards_recipient");
+
+            send_message(
+                &rpc_client,
+                &program_id,
+                arg_matches,
+                &mut wallet_manager,
+                &[
+                    sol_rewards_vault_program::instruction::set_rewards_recipient(
+                        program_id,
+                        vote_account,
+                        withdraw_authority,
+                        rewards_recipient,
+                    ),
+                ],
+                vec![fee_payer, withdraw_authority_signer],
+            )
+            .await?;
+        }
+        ("set-crank-mode", arg_matches) => {
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+            let (withdraw_authority_signer, withdraw_authority) = {
+                let (withdraw_authority_signer, withdraw_authority) =
+                    signer_or_address_of(arg_matches, "withdraw_authority", &mut wallet_manager)?;
+                (
+                    withdraw_authority_signer.expect("withdraw_authority_signer"),
+                    withdraw_authority.expect("withdraw_authority"),
+                )
+            };
+            let crank_enabled = arg_matches.value_of("crank_mode") == Some("on");
+
+            send_message(
+                &rpc_client,
+                &program_id,
+                arg_matches,
+                &mut wallet_manager,
+                &[sol_rewards_vault_program::instruction::set_crank_mode(
+                    program_id,
+                    vote_account,
+                    withdraw_authority,
+                    crank_enabled,
+                )],
+                vec![fee_payer, withdraw_authority_signer],
+            )
+            .await?;
+        }
+        ("crank", arg_matches) => {
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+            let rewards_recipient = pubkey_of(arg_matches, "rewards_recipient");
+
+            let rewards_recipient = if is_offline(arg_matches) {
+                rewards_recipient.ok_or(
+                    "error: REWARDS_RECIPIENT_ADDRESS is required with --sign-only or \
+                     --dump-message",
+                )?
+            } else {
+                let vault_state = get_vault_state(&rpc_client, &program_id, &vote_account).await?;
+                if vault_state.locked_rewards_recipient == Pubkey::default() {
+                    return Err(format!(
+                        "error: rewards recipient of {} is not locked",
+                        vote_account
+                    )
+                    .into());
+                }
+                if rewards_recipient.unwrap_or(vault_state.locked_rewards_recipient)
+                    != vault_state.locked_rewards_recipient
+                {
+                    return Err(format!(
+                        "error: rewards recipient of {} is locked to {}",
+                        vote_account, vault_state.locked_rewards_recipient
+                    )
+                    .into());
+                }
+                if get_withdrawable_lamports(&rpc_client, &vote_account, &vault_state).await? == 0 {
+                    println!("Nothing to withdraw");
+                    return Ok(());
+                }
+                vault_state.locked_rewards_recipient
+            };
+
+            send_message(
+                &rpc_client,
+                &program_id,
+                arg_matches,
+                &mut wallet_manager,
+                &[sol_rewards_vault_program::instruction::crank_rewards(
+                    program_id,
+                    vote_account,
+                    rewards_recipient,
+                )],
+                vec![fee_payer],
+            )
+            .await?;
+        }
+        ("update-commission", arg_matches) => {
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+            let (withdraw_authority_signer, withdraw_authority) = {
+                let (withdraw_authority_signer, withdraw_authority) =
+                    signer_or_address_of(arg_matches, "withdraw_authority", &mut wallet_manager)?;
+                (
+                    withdraw_authority_signer.expect("withdraw_authority_signer"),
+                    withdraw_authority.expect("withdraw_authority"),
+                )
+            };
+            let commission = value_of::<u8>(arg_matches, "commission").unwrap();
+
+            send_message(
+                &rpc_client,
+                &program_id,
+                arg_matches,
+                &mut wallet_manager,
+                &[sol_rewards_vault_program::instruction::update_commission(
+                    program_id,
+                    vote_account,
+                    withdraw_authority,
+                    commission,
+                )],
+                vec![fee_payer, withdraw_authority_signer],
+            )
+            .await?;
+        }
+        ("update-identity", arg_matches) => {
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+            let (withdraw_authority_signer, withdraw_authority) = {
+                let (withdraw_authority_signer, withdraw_authority) =
+                    signer_or_address_of(arg_matches, "withdraw_authority", &mut wallet_manager)?;
+                (
+                    withdraw_authority_signer.expect("withdraw_authority_signer"),
+                    withdraw_authority.expect("withdraw_authority"),
+                )
+            };
+            let (new_identity_signer, new_identity) = {
+                let (new_identity_signer, new_identity) =
+                    signer_or_address_of(arg_matches, "new_identity", &mut wallet_manager)?;
+                (
+                    new_identity_signer.expect("new_identity_signer"),
+                    new_identity.expect("new_identity"),
+                )
+            };
+
+            send_message(
+                &rpc_client,
+                &program_id,
+                arg_matches,
+                &mut wallet_manager,
+                &[
+                    sol_rewards_vault_program::instruction::update_validator_identity(
+                        program_id,
+                        vote_account,
+                        withdraw_authority,
+                        new_identity,
+                    ),
+                ],
+                vec![fee_payer, withdraw_authority_signer, new_identity_signer],
+            )
+            .await?;
+        }
+        ("authorize-voter", arg_matches) => {
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+            let (withdraw_authority_signer, withdraw_authority) = {
+                let (withdraw_authority_signer, withdraw_authority) =
+                    signer_or_address_of(arg_matches, "withdraw_authority", &mut wallet_manager)?;
+                (
+                    withdraw_authority_signer.expect("withdraw_authority_signer"),
+                    withdraw_authority.expect("withdraw_authority"),
+                )
+            };
+            let new_authorized_voter = pubkey_of(arg_matches, "new_authorized_voter").unwrap();
+
+            send_message(
+                &rpc_client,
+                &program_id,
+                arg_matches,
+                &mut wallet_manager,
+                &[sol_rewards_vault_program::instruction::authorize_voter(
+                    program_id,
+                    vote_account,
+                    withdraw_authority,
+                    new_authorized_voter,
+                )],
+                vec![fee_payer, withdraw_authority_signer],
+            )
+            .await?;
+        }
+        ("propose-withdraw-authority", arg_matches) => {
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+            let (withdraw_authority_signer, withdraw_authority) = {
+                let (withdraw_authority_signer, withdraw_authority) =
+                    signer_or_address_of(arg_matches, "withdraw_authority", &mut wallet_manager)?;
+                (
+                    withdraw_authority_signer.expect("withdraw_authority_signer"),
+                    withdraw_authority.expect("withdraw_authority"),
+                )
+            };
+            let new_withdraw_authority = pubkey_of(arg_matches, "new_withdraw_authority").unwrap();
+
+            send_message(
+                &rpc_client,
+                &program_id,
+                arg_matches,
+                &mut wallet_manager,
+                &[
+                    sol_rewards_vault_program::instruction::propose_withdraw_authority(
+                        program_id,
+                        vote_account,
+                        withdraw_authority,
+                        new_withdraw_authority,
+                    ),
+                ],
+                vec![fee_payer, withdraw_authority_signer],
+            )
+            .await?;
+        }
+        ("accept-withdraw-authority", arg_matches) => {
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+            let (new_withdraw_authority_signer, new_withdraw_authority) = {
+                let (new_withdraw_authority_signer, new_withdraw_authority) = signer_or_address_of(
+                    arg_matches,
+                    "new_withdraw_authority",
+                    &mut wallet_manager,
+                )?;
+                (
+                    new_withdraw_authority_signer.expect("new_withdraw_authority_signer"),
+                    new_withdraw_authority.expect("new_withdraw_authority"),
+                )
+            };
+
+            send_message(
+                &rpc_client,
+                &program_id,
+                arg_matches,
+                &mut wallet_manager,
+                &[
+                    sol_rewards_vault_program::instruction::accept_withdraw_authority(
+                        program_id,
+                        vote_account,
+                        new_withdraw_authority,
+                    ),
+                ],
+                vec![fee_payer, new_withdraw_authority_signer],
+            )
+            .await?;
+        }
+        ("cancel-withdraw-authority", arg_matches) => {
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+            let (withdraw_authority_signer, withdraw_authority) = {
+                let (withdraw_authority_signer, withdraw_authority) =
+                    signer_or_address_of(arg_matches, "withdraw_authority", &mut wallet_manager)?;
+                (
+                    withdraw_authority_signer.expect("withdraw_authority_signer"),
+                    withdraw_authority.expect("withdraw_authority"),
+                )
+            };
+
+            send_message(
+                &rpc_client,
+                &program_id,
+                arg_matches,
+                &mut wallet_manager,
+                &[
+                    sol_rewards_vault_program::instruction::cancel_withdraw_authority(
+                        program_id,
+                        vote_account,
+                        withdraw_authority,
+                    ),
+                ],
+                vec![fee_payer, withdraw_authority_signer],
+            )
+            .await?;
+        }
+        ("migrate", arg_matches) => {
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+
+            if !is_offline(arg_matches) {
+                let vault_state = get_vault_state(&rpc_client, &program_id, &vote_account).await?;
+                if vault_state.version == RewardsVaultState::VERSION {
+                    println!("Rewards vault is already version {}", vault_state.version);
+                    return Ok(());
+                }
+            }
+
+            send_message(
+                &rpc_client,
+                &program_id,
+                arg_matches,
+                &mut wallet_manager,
+                &[sol_rewards_vault_program::instruction::migrate(
+                    program_id,
+                    vote_account,
+                    fee_payer.pubkey(),
+                )],
+                vec![fee_payer],
+            )
+            .await?;
+        }
+        ("events", arg_matches) => {
+            let signature = Signature::from_str(arg_matches.value_of("signature").unwrap())?;
+
+            let events = get_events(&rpc_client, &program_id, &signature).await?;
+            if events.is_empty() {
+                println!("No rewards vault events");
+            }
+            for event in &events {
+                print_event(event);
+            }
+        }
+        ("list", arg_matches) => {
+            let withdraw_authority = pubkey_of(arg_matches, "withdraw_authority");
+            let rewards_authority = pubkey_of(arg_matches, "rewards_authority");
+
+            let vaults = get_vaults(
+                &rpc_client,
+                &program_id,
+                withdraw_authority,
+                rewards_authority,
+            )
+            .await?;
+            let vault_addresses = vaults
+                .iter()
+                .map(|(vault_address, _)| *vault_address)
+                .collect::<Vec<_>>();
+            let vault_vote_accounts =
+                get_vault_vote_accounts(&rpc_client, &program_id, &vault_addresses).await?;
+
+            let mut listings = vaults
+                .into_iter()
+                .map(|(vault_address, vault_state)| CliRewardsVaultListing {
+                    vote_account: vault_vote_accounts
+                        .get(&vault_address)
+                        .map(|vote_account| vote_account.to_string()),
+                    vault_address: vault_address.to_string(),
+                    version: vault_state.version,
+                    original_withdraw_authority: vault_state
+                        .original_withdraw_authority
+                        .to_string(),
+                    rewards_authority: vault_state.rewards_authority.to_string(),
+                })
+                .collect::<Vec<_>>();
+            listings.sort_by(|a, b| a.vote_account.cmp(&b.vote_account));
+
+            match arg_matches.value_of("output_format") {
+                Some("json") => println!("{}", serde_json::to_string_pretty(&listings).unwrap()),
+                Some("json-compact") => println!("{}", serde_json::to_string(&listings).unwrap()),
+                _ => {
+                    if listings.is_empty() {
+                        println!("No rewards vaults found");
+                    } else {
+                        println!(
+                            "{:<44}  {:<44}  {:<44}  Version",
+                            "Vote Account", "Withdraw Authority", "Rewards Authority"
+                        );
+                        for listing in &listings {
+                            println!(
+                                "{:<44}  {:<44}  {:<44}  {}",
+                                listing.vote_account.as_deref().unwrap_or("unknown"),
+                                listing.original_withdraw_authority,
+                                listing.rewards_authority,
+                                listing.version
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        ("show", arg_matches) => {
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+            let vault_address =
+                sol_rewards_vault_program::get_rewards_vault_address(&program_id, &vote_account);
+
+            let vault_state = get_vault_state(&rpc_client, &program_id, &vote_account).await?;
+            let vote_account_data = get_vote_account(&rpc_client, &vote_account).await?;
+            let vote_state = VoteState::deserialize(&vote_account_data.data)
+                .map_err(|err| format!("error: invalid vote account data: {}", err))?;
+            let rent_exempt_minimum = rpc_client
+                .get_minimum_balance_for_rent_exemption(vote_account_data.data.len())
+                .await
+                .map_err(|err| format!("error: unable to get rent-exempt minimum: {}", err))?;
+            let accounting_recorded = vault_state.version == RewardsVaultState::VERSION;
+
+            let cli_rewards_vault = CliRewardsVault {
+                vote_account: vote_account.to_string(),
+                vault_address: vault_address.to_string(),
+                version: vault_state.version,
+                original_withdraw_authority: vault_state.original_withdraw_authority.to_string(),
+                pending_withdraw_authority: (vault_state.pending_withdraw_authority
+                    != Pubkey::default())
+                .then(|| vault_state.pending_withdraw_authority.to_string()),
+                rewards_authority: vault_state.rewards_authority.to_string(),
+                reserve_lamports: vault_state.reserve_lamports,
+                locked_rewards_recipient: (vault_state.locked_rewards_recipient
+                    != Pubkey::default())
+                .then(|| vault_state.locked_rewards_recipient.to_string()),
+                crank_enabled: vault_state.crank_enabled != 0,
+                max_withdraw_per_epoch: (vault_state.max_withdraw_per_epoch != 0)
+                    .then_some(vault_state.max_withdraw_per_epoch),
+                withdrawn_this_epoch: vault_state.withdrawn_this_epoch,
+                last_withdraw_epoch: vault_state.last_withdraw_epoch,
+                split: vault_state
+                    .split()
+                    .into_iter()
+                    .map(|(recipient, basis_points)| CliSplitRecipient {
+                        recipient: recipient.to_string(),
+                        basis_points,
+                    })
+                    .collect(),
+                total_rewards_withdrawn: accounting_recorded
+                    .then_some(vault_state.total_rewards_withdrawn),
+                withdraw_count: accounting_recorded.then_some(vault_state.withdraw_count),
+                entered_epoch: (accounting_recorded && vault_state.entered_slot != 0)
+                    .then_some(vault_state.entered_epoch),
+                entered_slot: (accounting_recorded && vault_state.entered_slot != 0)
+                    .then_some(vault_state.entered_slot),
+                vote_account_withdrawer: vote_state.authorized_withdrawer.to_string(),
+                vote_account_in_vault: vote_state.authorized_withdrawer == vault_address,
+                vote_account_balance: vote_account_data.lamports,
+                rent_exempt_minimum,
+                withdrawable_lamports: vote_account_data.lamports.saturating_sub(
+                    rent_exempt_minimum.saturating_add(vault_state.reserve_lamports),
+                ),
+            };
+
+            match arg_matches.value_of("output_format") {
+                Some("json") => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&cli_rewards_vault).unwrap()
+                ),
+                Some("json-compact") => {
+                    println!("{}", serde_json::to_string(&cli_rewards_vault).unwrap())
+                }
+                _ => print_rewards_vault(&cli_rewards_vault),
+            }
         }
         _ => unreachable!(),
     };
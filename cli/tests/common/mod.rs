@@ -0,0 +1,102 @@
+use {
+    solana_client::rpc_client::RpcClient,
+    solana_sdk::{
+        commitment_config::CommitmentConfig,
+        pubkey::Pubkey,
+        signature::{write_keypair_file, Keypair, Signer},
+        transaction::Transaction,
+        vote::{
+            self,
+            state::{VoteInit, VoteState},
+        },
+    },
+    solana_test_validator::{TestValidator, TestValidatorGenesis},
+    std::{path::PathBuf, process::Command},
+};
+
+pub fn start_test_validator() -> (TestValidator, Keypair, RpcClient) {
+    let (test_validator, payer) = TestValidatorGenesis::default()
+        .add_program("sol_rewards_vault_program", sol_rewards_vault_program::id())
+        .start();
+    let rpc_client =
+        RpcClient::new_with_commitment(test_validator.rpc_url(), CommitmentConfig::confirmed());
+    (test_validator, payer, rpc_client)
+}
+
+pub fn keypair_file(keypair: &Keypair) -> String {
+    let path: PathBuf = std::env::temp_dir().join(format!("{}.json", keypair.pubkey()));
+    write_keypair_file(keypair, &path).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+pub fn send(
+    rpc_client: &RpcClient,
+    payer: &Keypair,
+    transaction: &mut Transaction,
+    signers: &[&Keypair],
+) {
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    transaction.sign(&all_signers, rpc_client.get_latest_blockhash().unwrap());
+    rpc_client
+        .send_and_confirm_transaction(transaction)
+        .unwrap();
+}
+
+pub fn create_vote_account(
+    rpc_client: &RpcClient,
+    payer: &Keypair,
+) -> (
+    /*vote_account_keypair*/ Keypair,
+    /*authorized_withdrawer_keypair*/ Keypair,
+) {
+    let node_pubkey_keypair = Keypair::new();
+    let vote_account_keypair = Keypair::new();
+    let authorized_withdrawer_keypair = Keypair::new();
+
+    let mut transaction = Transaction::new_with_payer(
+        &vote::instruction::create_account(
+            &payer.pubkey(),
+            &vote_account_keypair.pubkey(),
+            &VoteInit {
+                node_pubkey: node_pubkey_keypair.pubkey(),
+                authorized_voter: Pubkey::new_unique(),
+                authorized_withdrawer: authorized_withdrawer_keypair.pubkey(),
+                commission: 42,
+            },
+            rpc_client
+                .get_minimum_balance_for_rent_exemption(VoteState::size_of())
+                .unwrap(),
+        ),
+        Some(&payer.pubkey()),
+    );
+    send(
+        rpc_client,
+        payer,
+        &mut transaction,
+        &[&vote_account_keypair, &node_pubkey_keypair],
+    );
+
+    (vote_account_keypair, authorized_withdrawer_keypair)
+}
+
+pub fn run_cli(rpc_client: &RpcClient, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_sol-rewards-vault-cli"))
+        .arg("--url")
+        .arg(rpc_client.url())
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+pub fn get_vote_account_withdrawer(rpc_client: &RpcClient, vote_account: &Pubkey) -> Pubkey {
+    VoteState::deserialize(&rpc_client.get_account(vote_account).unwrap().data)
+        .unwrap()
+        .authorized_withdrawer
+}
@@ -0,0 +1,84 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use {
+    common::{
+        create_vote_account, get_vote_account_withdrawer, keypair_file, run_cli, send,
+        start_test_validator,
+    },
+    solana_sdk::{message::Message, pubkey::Pubkey, signature::Signer, transaction::Transaction},
+};
+
+#[test]
+fn test_dump_message() {
+    let (_test_validator, payer, rpc_client) = start_test_validator();
+    let payer_file = keypair_file(&payer);
+
+    let (vote_account_keypair, authorized_withdrawer_keypair) =
+        create_vote_account(&rpc_client, &payer);
+    let vote_account = vote_account_keypair.pubkey();
+    let withdraw_authority = authorized_withdrawer_keypair.pubkey();
+    let vault_address = sol_rewards_vault_program::get_rewards_vault_address(
+        &sol_rewards_vault_program::id(),
+        &vote_account,
+    );
+
+    // The withdraw authority is only known by its address, as for a multisig
+    let enter_message = run_cli(
+        &rpc_client,
+        &[
+            "--fee_payer",
+            &payer_file,
+            "enter",
+            "--dump-message",
+            "base64",
+            &vote_account.to_string(),
+            &withdraw_authority.to_string(),
+            &Pubkey::new_unique().to_string(),
+        ],
+    );
+    let enter_message: Message =
+        bincode::deserialize(&base64::decode(enter_message.trim()).unwrap()).unwrap();
+    assert_eq!(
+        get_vote_account_withdrawer(&rpc_client, &vote_account),
+        withdraw_authority
+    );
+
+    send(
+        &rpc_client,
+        &payer,
+        &mut Transaction::new_unsigned(enter_message),
+        &[&authorized_withdrawer_keypair],
+    );
+    assert_eq!(
+        get_vote_account_withdrawer(&rpc_client, &vote_account),
+        vault_address
+    );
+
+    let leave_message = run_cli(
+        &rpc_client,
+        &[
+            "--fee_payer",
+            &payer_file,
+            "leave",
+            "--dump-message",
+            "base58",
+            &vote_account.to_string(),
+            &withdraw_authority.to_string(),
+        ],
+    );
+    let leave_message: Message =
+        bincode::deserialize(&bs58::decode(leave_message.trim()).into_vec().unwrap()).unwrap();
+
+    send(
+        &rpc_client,
+        &payer,
+        &mut Transaction::new_unsigned(leave_message),
+        &[&authorized_withdrawer_keypair],
+    );
+    assert_eq!(
+        get_vote_account_withdrawer(&rpc_client, &vote_account),
+        withdraw_authority
+    );
+}
@@ -0,0 +1,180 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use {
+    common::{
+        create_vote_account, get_vote_account_withdrawer, keypair_file, run_cli, send,
+        start_test_validator,
+    },
+    solana_client::{nonce_utils, rpc_client::RpcClient},
+    solana_sdk::{
+        hash::Hash,
+        nonce::State,
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        system_instruction,
+        transaction::Transaction,
+    },
+    std::{thread::sleep, time::Duration},
+};
+
+/// Creates a nonce account and waits for a new blockhash so the nonce can be advanced
+fn create_nonce_account(
+    rpc_client: &RpcClient,
+    payer: &Keypair,
+    nonce_authority: &Pubkey,
+) -> Pubkey {
+    let nonce_account_keypair = Keypair::new();
+
+    let mut transaction = Transaction::new_with_payer(
+        &system_instruction::create_nonce_account(
+            &payer.pubkey(),
+            &nonce_account_keypair.pubkey(),
+            nonce_authority,
+            rpc_client
+                .get_minimum_balance_for_rent_exemption(State::size())
+                .unwrap(),
+        ),
+        Some(&payer.pubkey()),
+    );
+    send(
+        rpc_client,
+        payer,
+        &mut transaction,
+        &[&nonce_account_keypair],
+    );
+
+    let nonce_hash = get_nonce_hash(rpc_client, &nonce_account_keypair.pubkey());
+    while rpc_client.get_latest_blockhash().unwrap() == nonce_hash {
+        sleep(Duration::from_millis(100));
+    }
+    nonce_account_keypair.pubkey()
+}
+
+fn get_nonce_hash(rpc_client: &RpcClient, nonce_account: &Pubkey) -> Hash {
+    let account = nonce_utils::get_account_with_commitment(
+        rpc_client,
+        nonce_account,
+        rpc_client.commitment(),
+    )
+    .unwrap();
+    nonce_utils::data_from_account(&account)
+        .unwrap()
+        .blockhash()
+}
+
+#[test]
+fn test_enter_with_nonce() {
+    let (_test_validator, payer, rpc_client) = start_test_validator();
+    let payer_file = keypair_file(&payer);
+
+    let (vote_account_keypair, authorized_withdrawer_keypair) =
+        create_vote_account(&rpc_client, &payer);
+    let vote_account = vote_account_keypair.pubkey();
+    let nonce_account = create_nonce_account(&rpc_client, &payer, &payer.pubkey());
+    let nonce_hash = get_nonce_hash(&rpc_client, &nonce_account);
+
+    // The fee payer is the default nonce authority
+    run_cli(
+        &rpc_client,
+        &[
+            "--fee_payer",
+            &payer_file,
+            "enter",
+            "--nonce",
+            &nonce_account.to_string(),
+            &vote_account.to_string(),
+            &keypair_file(&authorized_withdrawer_keypair),
+            &Pubkey::new_unique().to_string(),
+        ],
+    );
+
+    assert_eq!(
+        get_vote_account_withdrawer(&rpc_client, &vote_account),
+        sol_rewards_vault_program::get_rewards_vault_address(
+            &sol_rewards_vault_program::id(),
+            &vote_account
+        )
+    );
+    assert_ne!(get_nonce_hash(&rpc_client, &nonce_account), nonce_hash);
+}
+
+#[test]
+fn test_leave_offline_with_nonce() {
+    let (_test_validator, payer, rpc_client) = start_test_validator();
+    let payer_file = keypair_file(&payer);
+    let nonce_authority_keypair = Keypair::new();
+
+    let (vote_account_keypair, authorized_withdrawer_keypair) =
+        create_vote_account(&rpc_client, &payer);
+    let vote_account = vote_account_keypair.pubkey();
+    let withdraw_authority = authorized_withdrawer_keypair.pubkey();
+    let nonce_account =
+        create_nonce_account(&rpc_client, &payer, &nonce_authority_keypair.pubkey());
+    let nonce_hash = get_nonce_hash(&rpc_client, &nonce_account);
+
+    run_cli(
+        &rpc_client,
+        &[
+            "--fee_payer",
+            &payer_file,
+            "enter",
+            &vote_account.to_string(),
+            &keypair_file(&authorized_withdrawer_keypair),
+            &Pubkey::new_unique().to_string(),
+        ],
+    );
+
+    // The withdraw authority signs offline
+    let sign_only_output = run_cli(
+        &rpc_client,
+        &[
+            "--fee_payer",
+            &payer.pubkey().to_string(),
+            "leave",
+            "--sign-only",
+            "--blockhash",
+            &nonce_hash.to_string(),
+            "--nonce",
+            &nonce_account.to_string(),
+            "--nonce-authority",
+            &nonce_authority_keypair.pubkey().to_string(),
+            &vote_account.to_string(),
+            &keypair_file(&authorized_withdrawer_keypair),
+        ],
+    );
+    let withdraw_authority_signer = sign_only_output
+        .lines()
+        .map(str::trim)
+        .find(|line| line.starts_with(&format!("{}=", withdraw_authority)))
+        .expect("withdraw authority signature")
+        .to_string();
+    assert!(sign_only_output.contains(&format!("Blockhash: {}", nonce_hash)));
+
+    // The fee payer and nonce authority sign and submit online
+    run_cli(
+        &rpc_client,
+        &[
+            "--fee_payer",
+            &payer_file,
+            "leave",
+            "--blockhash",
+            &nonce_hash.to_string(),
+            "--nonce",
+            &nonce_account.to_string(),
+            "--nonce-authority",
+            &keypair_file(&nonce_authority_keypair),
+            "--signer",
+            &withdraw_authority_signer,
+            &vote_account.to_string(),
+            &withdraw_authority.to_string(),
+        ],
+    );
+
+    assert_eq!(
+        get_vote_account_withdrawer(&rpc_client, &vote_account),
+        withdraw_authority
+    );
+    assert_ne!(get_nonce_hash(&rpc_client, &nonce_account), nonce_hash);
+}
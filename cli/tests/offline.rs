@@ -0,0 +1,95 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use {
+    common::{create_vote_account, keypair_file, run_cli, send, start_test_validator},
+    solana_client::rpc_client::RpcClient,
+    solana_sdk::{
+        native_token::LAMPORTS_PER_SOL,
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        system_instruction,
+        transaction::Transaction,
+    },
+};
+
+#[test]
+fn test_withdraw_offline() {
+    let (_test_validator, payer, rpc_client) = start_test_validator();
+    let payer_file = keypair_file(&payer);
+    let rewards_authority_keypair = Keypair::new();
+    let rewards_authority = rewards_authority_keypair.pubkey();
+    let rewards_recipient = Pubkey::new_unique();
+
+    let (vote_account_keypair, authorized_withdrawer_keypair) =
+        create_vote_account(&rpc_client, &payer);
+    let vote_account = vote_account_keypair.pubkey();
+
+    run_cli(
+        &rpc_client,
+        &[
+            "--fee_payer",
+            &payer_file,
+            "enter",
+            &vote_account.to_string(),
+            &keypair_file(&authorized_withdrawer_keypair),
+            &rewards_authority.to_string(),
+        ],
+    );
+
+    let mut transaction = Transaction::new_with_payer(
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            &vote_account,
+            LAMPORTS_PER_SOL,
+        )],
+        Some(&payer.pubkey()),
+    );
+    send(&rpc_client, &payer, &mut transaction, &[]);
+
+    // The rewards authority signs on a machine without access to the cluster
+    let blockhash = rpc_client.get_latest_blockhash().unwrap();
+    let sign_only_output = run_cli(
+        &RpcClient::new("http://127.0.0.1:1".to_string()),
+        &[
+            "--fee_payer",
+            &payer.pubkey().to_string(),
+            "withdraw",
+            "--sign-only",
+            "--blockhash",
+            &blockhash.to_string(),
+            &vote_account.to_string(),
+            &keypair_file(&rewards_authority_keypair),
+            &rewards_recipient.to_string(),
+        ],
+    );
+    let rewards_authority_signer = sign_only_output
+        .lines()
+        .map(str::trim)
+        .find(|line| line.starts_with(&format!("{}=", rewards_authority)))
+        .expect("rewards authority signature")
+        .to_string();
+
+    // The fee payer signs and submits online
+    run_cli(
+        &rpc_client,
+        &[
+            "--fee_payer",
+            &payer_file,
+            "withdraw",
+            "--blockhash",
+            &blockhash.to_string(),
+            "--signer",
+            &rewards_authority_signer,
+            &vote_account.to_string(),
+            &rewards_authority.to_string(),
+            &rewards_recipient.to_string(),
+        ],
+    );
+
+    assert_eq!(
+        rpc_client.get_balance(&rewards_recipient).unwrap(),
+        LAMPORTS_PER_SOL
+    );
+}
@@ -0,0 +1,502 @@
+//! Typed and validated accounts for each rewards vault instruction
+
+use {
+    crate::error::RewardsVaultError,
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        stake, system_program, sysvar, vote,
+    },
+    std::slice::Iter,
+};
+
+type AccountInfoIter<'a, 'b> = Iter<'a, AccountInfo<'b>>;
+
+pub(crate) fn check_writable(account_info: &AccountInfo) -> ProgramResult {
+    if !account_info.is_writable {
+        msg!("Account {} must be writable", account_info.key);
+        return Err(RewardsVaultError::AccountNotWritable.into());
+    }
+    Ok(())
+}
+
+fn check_signer(account_info: &AccountInfo) -> ProgramResult {
+    if !account_info.is_signer {
+        msg!("Account {} must sign", account_info.key);
+        return Err(RewardsVaultError::AccountNotSigner.into());
+    }
+    Ok(())
+}
+
+fn next_writable_account_info<'a, 'b>(
+    account_info_iter: &mut AccountInfoIter<'a, 'b>,
+) -> Result<&'a AccountInfo<'b>, ProgramError> {
+    let account_info = next_account_info(account_info_iter)?;
+    check_writable(account_info)?;
+    Ok(account_info)
+}
+
+fn next_signer_account_info<'a, 'b>(
+    account_info_iter: &mut AccountInfoIter<'a, 'b>,
+) -> Result<&'a AccountInfo<'b>, ProgramError> {
+    let account_info = next_account_info(account_info_iter)?;
+    check_signer(account_info)?;
+    Ok(account_info)
+}
+
+fn next_writable_signer_account_info<'a, 'b>(
+    account_info_iter: &mut AccountInfoIter<'a, 'b>,
+) -> Result<&'a AccountInfo<'b>, ProgramError> {
+    let account_info = next_writable_account_info(account_info_iter)?;
+    check_signer(account_info)?;
+    Ok(account_info)
+}
+
+fn next_account_info_with_key<'a, 'b>(
+    account_info_iter: &mut AccountInfoIter<'a, 'b>,
+    expected_key: &Pubkey,
+    error: RewardsVaultError,
+) -> Result<&'a AccountInfo<'b>, ProgramError> {
+    let account_info = next_account_info(account_info_iter)?;
+    if account_info.key != expected_key {
+        msg!(
+            "Expected account {}, got {}",
+            expected_key,
+            account_info.key
+        );
+        return Err(error.into());
+    }
+    Ok(account_info)
+}
+
+/// The vault and vote account that lead the accounts of every instruction
+pub(crate) struct VaultAccounts<'a, 'b> {
+    pub vault_info: &'a AccountInfo<'b>,
+    pub vote_account_info: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> VaultAccounts<'a, 'b> {
+    pub fn parse(account_info_iter: &mut AccountInfoIter<'a, 'b>) -> Result<Self, ProgramError> {
+        let vault_info = next_account_info(account_info_iter)?;
+        let vote_account_info = next_account_info(account_info_iter)?;
+
+        if *vote_account_info.owner != vote::program::id() {
+            msg!("{} is not a vote account", vote_account_info.key);
+            return Err(RewardsVaultError::IncorrectVoteAccountOwner.into());
+        }
+
+        Ok(Self {
+            vault_info,
+            vote_account_info,
+        })
+    }
+
+    fn check_vault_writable(&self) -> ProgramResult {
+        check_writable(self.vault_info)
+    }
+
+    fn check_vote_account_writable(&self) -> ProgramResult {
+        check_writable(self.vote_account_info)
+    }
+}
+
+pub(crate) struct EnterAccounts<'a, 'b> {
+    pub funder_info: &'a AccountInfo<'b>,
+    pub withdraw_authority_info: &'a AccountInfo<'b>,
+    pub rewards_authority_info: &'a AccountInfo<'b>,
+    pub system_program_info: &'a AccountInfo<'b>,
+    pub vote_program_info: &'a AccountInfo<'b>,
+    pub clock_sysvar_info: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> EnterAccounts<'a, 'b> {
+    pub fn parse(
+        vault_accounts: &VaultAccounts<'a, 'b>,
+        account_info_iter: &mut AccountInfoIter<'a, 'b>,
+    ) -> Result<Self, ProgramError> {
+        vault_accounts.check_vault_writable()?;
+        vault_accounts.check_vote_account_writable()?;
+        Ok(Self {
+            funder_info: next_writable_signer_account_info(account_info_iter)?,
+            withdraw_authority_info: next_signer_account_info(account_info_iter)?,
+            rewards_authority_info: next_account_info(account_info_iter)?,
+            system_program_info: next_account_info_with_key(
+                account_info_iter,
+                &system_program::id(),
+                RewardsVaultError::IncorrectSystemProgram,
+            )?,
+            vote_program_info: next_account_info_with_key(
+                account_info_iter,
+                &vote::program::id(),
+                RewardsVaultError::IncorrectVoteProgram,
+            )?,
+            clock_sysvar_info: next_account_info_with_key(
+                account_info_iter,
+                &sysvar::clock::id(),
+                RewardsVaultError::IncorrectSysvar,
+            )?,
+        })
+    }
+}
+
+pub(crate) struct LeaveAccounts<'a, 'b> {
+    pub refunder_info: &'a AccountInfo<'b>,
+    pub withdraw_authority_info: &'a AccountInfo<'b>,
+    pub vote_program_info: &'a AccountInfo<'b>,
+    pub clock_sysvar_info: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> LeaveAccounts<'a, 'b> {
+    pub fn parse(
+        vault_accounts: &VaultAccounts<'a, 'b>,
+        account_info_iter: &mut AccountInfoIter<'a, 'b>,
+    ) -> Result<Self, ProgramError> {
+        vault_accounts.check_vault_writable()?;
+        vault_accounts.check_vote_account_writable()?;
+        Ok(Self {
+            refunder_info: next_writable_account_info(account_info_iter)?,
+            withdraw_authority_info: next_signer_account_info(account_info_iter)?,
+            vote_program_info: next_account_info_with_key(
+                account_info_iter,
+                &vote::program::id(),
+                RewardsVaultError::IncorrectVoteProgram,
+            )?,
+            clock_sysvar_info: next_account_info_with_key(
+                account_info_iter,
+                &sysvar::clock::id(),
+                RewardsVaultError::IncorrectSysvar,
+            )?,
+        })
+    }
+}
+
+pub(crate) struct WithdrawRewardsAccounts<'a, 'b> {
+    pub rewards_recipient: &'a AccountInfo<'b>,
+    pub rewards_authority_info: &'a AccountInfo<'b>,
+    pub vote_program_info: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> WithdrawRewardsAccounts<'a, 'b> {
+    pub fn parse(
+        vault_accounts: &VaultAccounts<'a, 'b>,
+        account_info_iter: &mut AccountInfoIter<'a, 'b>,
+    ) -> Result<Self, ProgramError> {
+        // The vault is checked for writability only once it is known to be written to
+        vault_accounts.check_vote_account_writable()?;
+        Ok(Self {
+            rewards_recipient: next_writable_account_info(account_info_iter)?,
+            rewards_authority_info: next_signer_account_info(account_info_iter)?,
+            vote_program_info: next_account_info_with_key(
+                account_info_iter,
+                &vote::program::id(),
+                RewardsVaultError::IncorrectVoteProgram,
+            )?,
+        })
+    }
+}
+
+pub(crate) struct CrankRewardsAccounts<'a, 'b> {
+    pub rewards_recipient: &'a AccountInfo<'b>,
+    pub vote_program_info: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> CrankRewardsAccounts<'a, 'b> {
+    pub fn parse(
+        vault_accounts: &VaultAccounts<'a, 'b>,
+        account_info_iter: &mut AccountInfoIter<'a, 'b>,
+    ) -> Result<Self, ProgramError> {
+        // The vault is checked for writability only once it is known to be written to
+        vault_accounts.check_vote_account_writable()?;
+        Ok(Self {
+            rewards_recipient: next_writable_account_info(account_info_iter)?,
+            vote_program_info: next_account_info_with_key(
+                account_info_iter,
+                &vote::program::id(),
+                RewardsVaultError::IncorrectVoteProgram,
+            )?,
+        })
+    }
+}
+
+/// Accounts of `CancelWithdrawAuthority`, `SetReserve`, `SetCrankMode` and `SetWithdrawLimit`
+pub(crate) struct SetVaultStateAccounts<'a, 'b> {
+    pub withdraw_authority_info: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> SetVaultStateAccounts<'a, 'b> {
+    pub fn parse(
+        vault_accounts: &VaultAccounts<'a, 'b>,
+        account_info_iter: &mut AccountInfoIter<'a, 'b>,
+    ) -> Result<Self, ProgramError> {
+        vault_accounts.check_vault_writable()?;
+        Ok(Self {
+            withdraw_authority_info: next_signer_account_info(account_info_iter)?,
+        })
+    }
+}
+
+/// Accounts of `SetRewardsAuthority` and `ProposeWithdrawAuthority`
+pub(crate) struct SetAuthorityAccounts<'a, 'b> {
+    pub withdraw_authority_info: &'a AccountInfo<'b>,
+    pub new_authority_info: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> SetAuthorityAccounts<'a, 'b> {
+    pub fn parse(
+        vault_accounts: &VaultAccounts<'a, 'b>,
+        account_info_iter: &mut AccountInfoIter<'a, 'b>,
+    ) -> Result<Self, ProgramError> {
+        vault_accounts.check_vault_writable()?;
+        Ok(Self {
+            withdraw_authority_info: next_signer_account_info(account_info_iter)?,
+            new_authority_info: next_account_info(account_info_iter)?,
+        })
+    }
+}
+
+pub(crate) struct AcceptWithdrawAuthorityAccounts<'a, 'b> {
+    pub new_withdraw_authority_info: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> AcceptWithdrawAuthorityAccounts<'a, 'b> {
+    pub fn parse(
+        vault_accounts: &VaultAccounts<'a, 'b>,
+        account_info_iter: &mut AccountInfoIter<'a, 'b>,
+    ) -> Result<Self, ProgramError> {
+        vault_accounts.check_vault_writable()?;
+        Ok(Self {
+            new_withdraw_authority_info: next_signer_account_info(account_info_iter)?,
+        })
+    }
+}
+
+pub(crate) struct UpdateCommissionAccounts<'a, 'b> {
+    pub withdraw_authority_info: &'a AccountInfo<'b>,
+    pub vote_program_info: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> UpdateCommissionAccounts<'a, 'b> {
+    pub fn parse(
+        vault_accounts: &VaultAccounts<'a, 'b>,
+        account_info_iter: &mut AccountInfoIter<'a, 'b>,
+    ) -> Result<Self, ProgramError> {
+        vault_accounts.check_vote_account_writable()?;
+        Ok(Self {
+            withdraw_authority_info: next_signer_account_info(account_info_iter)?,
+            vote_program_info: next_account_info_with_key(
+                account_info_iter,
+                &vote::program::id(),
+                RewardsVaultError::IncorrectVoteProgram,
+            )?,
+        })
+    }
+}
+
+pub(crate) struct UpdateValidatorIdentityAccounts<'a, 'b> {
+    pub withdraw_authority_info: &'a AccountInfo<'b>,
+    pub new_identity_info: &'a AccountInfo<'b>,
+    pub vote_program_info: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> UpdateValidatorIdentityAccounts<'a, 'b> {
+    pub fn parse(
+        vault_accounts: &VaultAccounts<'a, 'b>,
+        account_info_iter: &mut AccountInfoIter<'a, 'b>,
+    ) -> Result<Self, ProgramError> {
+        vault_accounts.check_vote_account_writable()?;
+        Ok(Self {
+            withdraw_authority_info: next_signer_account_info(account_info_iter)?,
+            new_identity_info: next_signer_account_info(account_info_iter)?,
+            vote_program_info: next_account_info_with_key(
+                account_info_iter,
+                &vote::program::id(),
+                RewardsVaultError::IncorrectVoteProgram,
+            )?,
+        })
+    }
+}
+
+pub(crate) struct AuthorizeVoterAccounts<'a, 'b> {
+    pub withdraw_authority_info: &'a AccountInfo<'b>,
+    pub new_authorized_voter_info: &'a AccountInfo<'b>,
+    pub vote_program_info: &'a AccountInfo<'b>,
+    pub clock_sysvar_info: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> AuthorizeVoterAccounts<'a, 'b> {
+    pub fn parse(
+        vault_accounts: &VaultAccounts<'a, 'b>,
+        account_info_iter: &mut AccountInfoIter<'a, 'b>,
+    ) -> Result<Self, ProgramError> {
+        vault_accounts.check_vote_account_writable()?;
+        Ok(Self {
+            withdraw_authority_info: next_signer_account_info(account_info_iter)?,
+            new_authorized_voter_info: next_account_info(account_info_iter)?,
+            vote_program_info: next_account_info_with_key(
+                account_info_iter,
+                &vote::program::id(),
+                RewardsVaultError::IncorrectVoteProgram,
+            )?,
+            clock_sysvar_info: next_account_info_with_key(
+                account_info_iter,
+                &sysvar::clock::id(),
+                RewardsVaultError::IncorrectSysvar,
+            )?,
+        })
+    }
+}
+
+pub(crate) struct SetRewardsRecipientAccounts<'a, 'b> {
+    pub withdraw_authority_info: &'a AccountInfo<'b>,
+    pub rewards_recipient_info: Option<&'a AccountInfo<'b>>,
+}
+
+impl<'a, 'b> SetRewardsRecipientAccounts<'a, 'b> {
+    pub fn parse(
+        vault_accounts: &VaultAccounts<'a, 'b>,
+        account_info_iter: &mut AccountInfoIter<'a, 'b>,
+    ) -> Result<Self, ProgramError> {
+        vault_accounts.check_vault_writable()?;
+        Ok(Self {
+            withdraw_authority_info: next_signer_account_info(account_info_iter)?,
+            rewards_recipient_info: account_info_iter.next(),
+        })
+    }
+}
+
+pub(crate) struct SetSplitAccounts<'a, 'b> {
+    pub withdraw_authority_info: &'a AccountInfo<'b>,
+    pub split_recipient_infos: &'a [AccountInfo<'b>],
+}
+
+impl<'a, 'b> SetSplitAccounts<'a, 'b> {
+    pub fn parse(
+        vault_accounts: &VaultAccounts<'a, 'b>,
+        account_info_iter: &mut AccountInfoIter<'a, 'b>,
+    ) -> Result<Self, ProgramError> {
+        vault_accounts.check_vault_writable()?;
+        Ok(Self {
+            withdraw_authority_info: next_signer_account_info(account_info_iter)?,
+            split_recipient_infos: account_info_iter.as_slice(),
+        })
+    }
+}
+
+pub(crate) struct WithdrawRewardsSplitAccounts<'a, 'b> {
+    pub rewards_authority_info: &'a AccountInfo<'b>,
+    pub vote_program_info: &'a AccountInfo<'b>,
+    pub split_recipient_infos: &'a [AccountInfo<'b>],
+}
+
+impl<'a, 'b> WithdrawRewardsSplitAccounts<'a, 'b> {
+    pub fn parse(
+        vault_accounts: &VaultAccounts<'a, 'b>,
+        account_info_iter: &mut AccountInfoIter<'a, 'b>,
+    ) -> Result<Self, ProgramError> {
+        vault_accounts.check_vault_writable()?;
+        vault_accounts.check_vote_account_writable()?;
+        let rewards_authority_info = next_signer_account_info(account_info_iter)?;
+        let vote_program_info = next_account_info_with_key(
+            account_info_iter,
+            &vote::program::id(),
+            RewardsVaultError::IncorrectVoteProgram,
+        )?;
+        let split_recipient_infos = account_info_iter.as_slice();
+        for split_recipient_info in split_recipient_infos {
+            check_writable(split_recipient_info)?;
+        }
+        Ok(Self {
+            rewards_authority_info,
+            vote_program_info,
+            split_recipient_infos,
+        })
+    }
+}
+
+pub(crate) struct WithdrawAndStakeAccounts<'a, 'b> {
+    pub stake_account_info: &'a AccountInfo<'b>,
+    pub rewards_authority_info: &'a AccountInfo<'b>,
+    pub stake_withdraw_authority_info: &'a AccountInfo<'b>,
+    pub vote_program_info: &'a AccountInfo<'b>,
+    pub system_program_info: &'a AccountInfo<'b>,
+    pub stake_program_info: &'a AccountInfo<'b>,
+    pub rent_sysvar_info: &'a AccountInfo<'b>,
+    pub clock_sysvar_info: &'a AccountInfo<'b>,
+    pub stake_history_sysvar_info: &'a AccountInfo<'b>,
+    pub stake_config_info: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> WithdrawAndStakeAccounts<'a, 'b> {
+    pub fn parse(
+        vault_accounts: &VaultAccounts<'a, 'b>,
+        account_info_iter: &mut AccountInfoIter<'a, 'b>,
+    ) -> Result<Self, ProgramError> {
+        vault_accounts.check_vault_writable()?;
+        vault_accounts.check_vote_account_writable()?;
+        Ok(Self {
+            stake_account_info: next_writable_signer_account_info(account_info_iter)?,
+            rewards_authority_info: next_signer_account_info(account_info_iter)?,
+            stake_withdraw_authority_info: next_account_info(account_info_iter)?,
+            vote_program_info: next_account_info_with_key(
+                account_info_iter,
+                &vote::program::id(),
+                RewardsVaultError::IncorrectVoteProgram,
+            )?,
+            system_program_info: next_account_info_with_key(
+                account_info_iter,
+                &system_program::id(),
+                RewardsVaultError::IncorrectSystemProgram,
+            )?,
+            stake_program_info: next_account_info_with_key(
+                account_info_iter,
+                &stake::program::id(),
+                RewardsVaultError::IncorrectStakeProgram,
+            )?,
+            rent_sysvar_info: next_account_info_with_key(
+                account_info_iter,
+                &sysvar::rent::id(),
+                RewardsVaultError::IncorrectSysvar,
+            )?,
+            clock_sysvar_info: next_account_info_with_key(
+                account_info_iter,
+                &sysvar::clock::id(),
+                RewardsVaultError::IncorrectSysvar,
+            )?,
+            stake_history_sysvar_info: next_account_info_with_key(
+                account_info_iter,
+                &sysvar::stake_history::id(),
+                RewardsVaultError::IncorrectSysvar,
+            )?,
+            stake_config_info: next_account_info_with_key(
+                account_info_iter,
+                &stake::config::id(),
+                RewardsVaultError::IncorrectStakeConfig,
+            )?,
+        })
+    }
+}
+
+pub(crate) struct MigrateAccounts<'a, 'b> {
+    pub funder_info: &'a AccountInfo<'b>,
+    pub system_program_info: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> MigrateAccounts<'a, 'b> {
+    pub fn parse(
+        vault_accounts: &VaultAccounts<'a, 'b>,
+        account_info_iter: &mut AccountInfoIter<'a, 'b>,
+    ) -> Result<Self, ProgramError> {
+        vault_accounts.check_vault_writable()?;
+        Ok(Self {
+            funder_info: next_writable_signer_account_info(account_info_iter)?,
+            system_program_info: next_account_info_with_key(
+                account_info_iter,
+                &system_program::id(),
+                RewardsVaultError::IncorrectSystemProgram,
+            )?,
+        })
+    }
+}
@@ -1,7 +1,11 @@
 #![cfg(not(feature = "no-entrypoint"))]
 
-use solana_program::{
-    account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, pubkey::Pubkey,
+use {
+    crate::error::RewardsVaultError,
+    solana_program::{
+        account_info::AccountInfo, entrypoint, entrypoint::ProgramResult,
+        program_error::PrintProgramError, pubkey::Pubkey,
+    },
 };
 
 entrypoint!(process_instruction);
@@ -10,5 +14,11 @@ fn process_instruction(
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    crate::processor::process_instruction(program_id, accounts, instruction_data)
+    if let Err(error) =
+        crate::processor::process_instruction(program_id, accounts, instruction_data)
+    {
+        error.print::<RewardsVaultError>();
+        return Err(error);
+    }
+    Ok(())
 }
@@ -0,0 +1,95 @@
+use {
+    num_derive::FromPrimitive,
+    solana_program::{
+        decode_error::DecodeError,
+        msg,
+        program_error::{PrintProgramError, ProgramError},
+    },
+    thiserror::Error,
+};
+
+/// Errors that may be returned by the rewards vault program
+#[derive(Clone, Copy, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum RewardsVaultError {
+    #[error("Vault address does not match the vote account")]
+    VaultAddressMismatch,
+    #[error("Vote account already resides in its rewards vault")]
+    AlreadyInVault,
+    #[error("Wrong withdraw authority")]
+    WrongWithdrawAuthority,
+    #[error("Wrong rewards authority")]
+    WrongRewardsAuthority,
+    #[error("No withdraw authority has been proposed")]
+    NoPendingWithdrawAuthority,
+    #[error("Wrong pending withdraw authority")]
+    WrongPendingWithdrawAuthority,
+    #[error("Vote account balance is below its rent-exempt minimum")]
+    VoteAccountBelowMinimumBalance,
+    #[error("Requested amount exceeds the withdrawable rewards")]
+    InsufficientRewards,
+    #[error("Per-epoch withdraw limit exceeded")]
+    WithdrawLimitExceeded,
+    #[error("Rewards recipient is locked to another account")]
+    RewardsRecipientLocked,
+    #[error("Rewards recipient is not locked")]
+    RewardsRecipientNotLocked,
+    #[error("Crank is not enabled")]
+    CrankNotEnabled,
+    #[error("Invalid rewards split")]
+    InvalidSplit,
+    #[error("No rewards split configured")]
+    NoSplitConfigured,
+    #[error("Split recipients do not match the configured rewards split")]
+    SplitRecipientMismatch,
+    #[error("Vault must be migrated to the current layout first")]
+    MigrationRequired,
+    #[error("Vault account is not owned by the rewards vault program")]
+    IncorrectVaultOwner,
+    #[error("Vote account is not owned by the vote program")]
+    IncorrectVoteAccountOwner,
+    #[error("Account must be writable")]
+    AccountNotWritable,
+    #[error("Incorrect vote program")]
+    IncorrectVoteProgram,
+    #[error("Incorrect system program")]
+    IncorrectSystemProgram,
+    #[error("Incorrect stake program")]
+    IncorrectStakeProgram,
+    #[error("Incorrect sysvar account")]
+    IncorrectSysvar,
+    #[error("Incorrect stake config account")]
+    IncorrectStakeConfig,
+    #[error("Commission must not exceed 100%")]
+    InvalidCommission,
+    #[error("Stake account already exists")]
+    StakeAccountInUse,
+    #[error("Stake is below the stake account rent-exempt reserve and minimum delegation")]
+    InsufficientStakeAmount,
+    #[error("Account must sign the transaction")]
+    AccountNotSigner,
+}
+
+impl From<RewardsVaultError> for ProgramError {
+    fn from(e: RewardsVaultError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+impl<T> DecodeError<T> for RewardsVaultError {
+    fn type_of() -> &'static str {
+        "RewardsVaultError"
+    }
+}
+
+impl PrintProgramError for RewardsVaultError {
+    fn print<E>(&self)
+    where
+        E: 'static
+            + std::error::Error
+            + DecodeError<E>
+            + PrintProgramError
+            + num_traits::FromPrimitive,
+    {
+        msg!("Error: {}", self);
+    }
+}
@@ -0,0 +1,569 @@
+//! Structured events logged by the rewards vault program with `sol_log_data`
+//!
+//! Each event is logged as a single data field: a one byte event type followed by the event
+//! fields, public keys as 32 bytes, integers as little-endian at their native width and booleans
+//! as a single byte of 0 or 1.
+
+use {
+    crate::state::MAX_SPLIT_RECIPIENTS,
+    solana_program::{clock::Epoch, log::sol_log_data, pubkey::Pubkey},
+};
+
+const LOG_INVOKE_SUFFIX: &str = " invoke [";
+const LOG_DATA_PREFIX: &str = "Program data: ";
+
+/// Writes the fields of a packed event in order
+struct EventData(Vec<u8>);
+
+impl EventData {
+    fn pubkey(&mut self, pubkey: &Pubkey) -> &mut Self {
+        self.0.extend_from_slice(pubkey.as_ref());
+        self
+    }
+
+    fn u64(&mut self, value: u64) -> &mut Self {
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn u16(&mut self, value: u16) -> &mut Self {
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn u8(&mut self, value: u8) -> &mut Self {
+        self.0.push(value);
+        self
+    }
+
+    fn bool(&mut self, value: bool) -> &mut Self {
+        self.u8(u8::from(value))
+    }
+}
+
+/// Reads the fields of a packed event in order
+struct EventFields<'a>(&'a [u8]);
+
+impl EventFields<'_> {
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        if self.0.len() < N {
+            return None;
+        }
+        let (field, rest) = self.0.split_at(N);
+        self.0 = rest;
+        field.try_into().ok()
+    }
+
+    fn pubkey(&mut self) -> Option<Pubkey> {
+        self.take().map(Pubkey::new_from_array)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take().map(u64::from_le_bytes)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take().map(u16::from_le_bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take().map(u8::from_le_bytes)
+    }
+
+    fn bool(&mut self) -> Option<bool> {
+        match self.u8()? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RewardsVaultEvent {
+    /// A vote account was placed in its rewards vault
+    Enter {
+        vote_account: Pubkey,
+        withdraw_authority: Pubkey,
+        rewards_authority: Pubkey,
+        reserve_lamports: u64,
+        epoch: Epoch,
+    },
+    /// A vote account was removed from its rewards vault
+    Leave {
+        vote_account: Pubkey,
+        withdraw_authority: Pubkey,
+        epoch: Epoch,
+    },
+    /// Epoch rewards were withdrawn from a vote account, logged once per recipient. Rewards
+    /// withdrawn into a new stake account are reported with the stake account as the recipient
+    WithdrawRewards {
+        vote_account: Pubkey,
+        rewards_recipient: Pubkey,
+        lamports: u64,
+        epoch: Epoch,
+    },
+    /// A new rewards authority was installed
+    SetRewardsAuthority {
+        vote_account: Pubkey,
+        rewards_authority: Pubkey,
+        epoch: Epoch,
+    },
+    /// A new withdraw authority was proposed
+    ProposeWithdrawAuthority {
+        vote_account: Pubkey,
+        pending_withdraw_authority: Pubkey,
+        epoch: Epoch,
+    },
+    /// The proposed withdraw authority accepted the nomination
+    AcceptWithdrawAuthority {
+        vote_account: Pubkey,
+        withdraw_authority: Pubkey,
+        epoch: Epoch,
+    },
+    /// The pending withdraw authority nomination was cancelled
+    CancelWithdrawAuthority { vote_account: Pubkey, epoch: Epoch },
+    /// The validator identity of the vote account was changed
+    UpdateValidatorIdentity {
+        vote_account: Pubkey,
+        validator_identity: Pubkey,
+        epoch: Epoch,
+    },
+    /// A new authorized voter was installed on the vote account
+    AuthorizeVoter {
+        vote_account: Pubkey,
+        authorized_voter: Pubkey,
+        epoch: Epoch,
+    },
+    /// A new reserve was set
+    SetReserve {
+        vote_account: Pubkey,
+        reserve_lamports: u64,
+        epoch: Epoch,
+    },
+    /// The rewards recipient was locked, or unlocked if it is the default public key
+    SetRewardsRecipient {
+        vote_account: Pubkey,
+        rewards_recipient: Pubkey,
+        epoch: Epoch,
+    },
+    /// Permissionless cranking of rewards was enabled or disabled
+    SetCrankMode {
+        vote_account: Pubkey,
+        crank_enabled: bool,
+        epoch: Epoch,
+    },
+    /// A new per-epoch withdraw limit was set, zero for no limit
+    SetWithdrawLimit {
+        vote_account: Pubkey,
+        max_withdraw_per_epoch: u64,
+        epoch: Epoch,
+    },
+    /// A new rewards split was set, unused entries are the default public key with zero basis
+    /// points
+    SetSplit {
+        vote_account: Pubkey,
+        split_recipients: [Pubkey; MAX_SPLIT_RECIPIENTS],
+        split_bps: [u16; MAX_SPLIT_RECIPIENTS],
+        epoch: Epoch,
+    },
+    /// The commission of the vote account was changed
+    UpdateCommission {
+        vote_account: Pubkey,
+        commission: u8,
+        epoch: Epoch,
+    },
+    /// The vault was migrated to the current state layout
+    Migrate {
+        vote_account: Pubkey,
+        previous_version: u8,
+        version: u8,
+        epoch: Epoch,
+    },
+}
+
+impl RewardsVaultEvent {
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data = EventData(vec![]);
+        match self {
+            Self::Enter {
+                vote_account,
+                withdraw_authority,
+                rewards_authority,
+                reserve_lamports,
+                epoch,
+            } => data
+                .u8(0)
+                .pubkey(vote_account)
+                .pubkey(withdraw_authority)
+                .pubkey(rewards_authority)
+                .u64(*reserve_lamports)
+                .u64(*epoch),
+            Self::Leave {
+                vote_account,
+                withdraw_authority,
+                epoch,
+            } => data
+                .u8(1)
+                .pubkey(vote_account)
+                .pubkey(withdraw_authority)
+                .u64(*epoch),
+            Self::WithdrawRewards {
+                vote_account,
+                rewards_recipient,
+                lamports,
+                epoch,
+            } => data
+                .u8(2)
+                .pubkey(vote_account)
+                .pubkey(rewards_recipient)
+                .u64(*lamports)
+                .u64(*epoch),
+            Self::SetRewardsAuthority {
+                vote_account,
+                rewards_authority,
+                epoch,
+            } => data
+                .u8(3)
+                .pubkey(vote_account)
+                .pubkey(rewards_authority)
+                .u64(*epoch),
+            Self::ProposeWithdrawAuthority {
+                vote_account,
+                pending_withdraw_authority,
+                epoch,
+            } => data
+                .u8(4)
+                .pubkey(vote_account)
+                .pubkey(pending_withdraw_authority)
+                .u64(*epoch),
+            Self::AcceptWithdrawAuthority {
+                vote_account,
+                withdraw_authority,
+                epoch,
+            } => data
+                .u8(5)
+                .pubkey(vote_account)
+                .pubkey(withdraw_authority)
+                .u64(*epoch),
+            Self::CancelWithdrawAuthority {
+                vote_account,
+                epoch,
+            } => data.u8(6).pubkey(vote_account).u64(*epoch),
+            Self::UpdateValidatorIdentity {
+                vote_account,
+                validator_identity,
+                epoch,
+            } => data
+                .u8(7)
+                .pubkey(vote_account)
+                .pubkey(validator_identity)
+                .u64(*epoch),
+            Self::AuthorizeVoter {
+                vote_account,
+                authorized_voter,
+                epoch,
+            } => data
+                .u8(8)
+                .pubkey(vote_account)
+                .pubkey(authorized_voter)
+                .u64(*epoch),
+            Self::SetReserve {
+                vote_account,
+                reserve_lamports,
+                epoch,
+            } => data
+                .u8(9)
+                .pubkey(vote_account)
+                .u64(*reserve_lamports)
+                .u64(*epoch),
+            Self::SetRewardsRecipient {
+                vote_account,
+                rewards_recipient,
+                epoch,
+            } => data
+                .u8(10)
+                .pubkey(vote_account)
+                .pubkey(rewards_recipient)
+                .u64(*epoch),
+            Self::SetCrankMode {
+                vote_account,
+                crank_enabled,
+                epoch,
+            } => data
+                .u8(11)
+                .pubkey(vote_account)
+                .bool(*crank_enabled)
+                .u64(*epoch),
+            Self::SetWithdrawLimit {
+                vote_account,
+                max_withdraw_per_epoch,
+                epoch,
+            } => data
+                .u8(12)
+                .pubkey(vote_account)
+                .u64(*max_withdraw_per_epoch)
+                .u64(*epoch),
+            Self::SetSplit {
+                vote_account,
+                split_recipients,
+                split_bps,
+                epoch,
+            } => {
+                data.u8(13).pubkey(vote_account);
+                for split_recipient in split_recipients {
+                    data.pubkey(split_recipient);
+                }
+                for bps in split_bps {
+                    data.u16(*bps);
+                }
+                data.u64(*epoch)
+            }
+            Self::UpdateCommission {
+                vote_account,
+                commission,
+                epoch,
+            } => data.u8(14).pubkey(vote_account).u8(*commission).u64(*epoch),
+            Self::Migrate {
+                vote_account,
+                previous_version,
+                version,
+                epoch,
+            } => data
+                .u8(15)
+                .pubkey(vote_account)
+                .u8(*previous_version)
+                .u8(*version)
+                .u64(*epoch),
+        };
+        data.0
+    }
+
+    pub fn unpack(data: &[u8]) -> Option<Self> {
+        let (event_type, data) = data.split_first()?;
+        let mut fields = EventFields(data);
+        let event = match event_type {
+            0 => Self::Enter {
+                vote_account: fields.pubkey()?,
+                withdraw_authority: fields.pubkey()?,
+                rewards_authority: fields.pubkey()?,
+                reserve_lamports: fields.u64()?,
+                epoch: fields.u64()?,
+            },
+            1 => Self::Leave {
+                vote_account: fields.pubkey()?,
+                withdraw_authority: fields.pubkey()?,
+                epoch: fields.u64()?,
+            },
+            2 => Self::WithdrawRewards {
+                vote_account: fields.pubkey()?,
+                rewards_recipient: fields.pubkey()?,
+                lamports: fields.u64()?,
+                epoch: fields.u64()?,
+            },
+            3 => Self::SetRewardsAuthority {
+                vote_account: fields.pubkey()?,
+                rewards_authority: fields.pubkey()?,
+                epoch: fields.u64()?,
+            },
+            4 => Self::ProposeWithdrawAuthority {
+                vote_account: fields.pubkey()?,
+                pending_withdraw_authority: fields.pubkey()?,
+                epoch: fields.u64()?,
+            },
+            5 => Self::AcceptWithdrawAuthority {
+                vote_account: fields.pubkey()?,
+                withdraw_authority: fields.pubkey()?,
+                epoch: fields.u64()?,
+            },
+            6 => Self::CancelWithdrawAuthority {
+                vote_account: fields.pubkey()?,
+                epoch: fields.u64()?,
+            },
+            7 => Self::UpdateValidatorIdentity {
+                vote_account: fields.pubkey()?,
+                validator_identity: fields.pubkey()?,
+                epoch: fields.u64()?,
+            },
+            8 => Self::AuthorizeVoter {
+                vote_account: fields.pubkey()?,
+                authorized_voter: fields.pubkey()?,
+                epoch: fields.u64()?,
+            },
+            9 => Self::SetReserve {
+                vote_account: fields.pubkey()?,
+                reserve_lamports: fields.u64()?,
+                epoch: fields.u64()?,
+            },
+            10 => Self::SetRewardsRecipient {
+                vote_account: fields.pubkey()?,
+                rewards_recipient: fields.pubkey()?,
+                epoch: fields.u64()?,
+            },
+            11 => Self::SetCrankMode {
+                vote_account: fields.pubkey()?,
+                crank_enabled: fields.bool()?,
+                epoch: fields.u64()?,
+            },
+            12 => Self::SetWithdrawLimit {
+                vote_account: fields.pubkey()?,
+                max_withdraw_per_epoch: fields.u64()?,
+                epoch: fields.u64()?,
+            },
+            13 => {
+                let vote_account = fields.pubkey()?;
+                let mut split_recipients = [Pubkey::default(); MAX_SPLIT_RECIPIENTS];
+                for split_recipient in &mut split_recipients {
+                    *split_recipient = fields.pubkey()?;
+                }
+                let mut split_bps = [0; MAX_SPLIT_RECIPIENTS];
+                for bps in &mut split_bps {
+                    *bps = fields.u16()?;
+                }
+                Self::SetSplit {
+                    vote_account,
+                    split_recipients,
+                    split_bps,
+                    epoch: fields.u64()?,
+                }
+            }
+            14 => Self::UpdateCommission {
+                vote_account: fields.pubkey()?,
+                commission: fields.u8()?,
+                epoch: fields.u64()?,
+            },
+            15 => Self::Migrate {
+                vote_account: fields.pubkey()?,
+                previous_version: fields.u8()?,
+                version: fields.u8()?,
+                epoch: fields.u64()?,
+            },
+            _ => return None,
+        };
+        if !fields.0.is_empty() {
+            return None;
+        }
+        Some(event)
+    }
+
+    /// Logs the event as program data
+    pub(crate) fn emit(&self) {
+        sol_log_data(&[&self.pack()]);
+    }
+}
+
+/// Decodes the events logged by the rewards vault program at `program_id` from the log messages
+/// of a transaction. Program data logged by other programs is ignored
+pub fn parse_logs(program_id: &Pubkey, log_messages: &[String]) -> Vec<RewardsVaultEvent> {
+    let program_id = program_id.to_string();
+    let mut invoked_programs = vec![];
+    let mut events = vec![];
+
+    for log_message in log_messages {
+        if let Some(data) = log_message.strip_prefix(LOG_DATA_PREFIX) {
+            if invoked_programs.last() == Some(&program_id.as_str()) {
+                events.extend(
+                    data.split(' ')
+                        .filter_map(|field| base64::decode(field).ok())
+                        .filter_map(|field| RewardsVaultEvent::unpack(&field)),
+                );
+            }
+        } else if let Some(program) = log_message.strip_prefix("Program ") {
+            if let Some((invoked_program, _)) = program.split_once(LOG_INVOKE_SUFFIX) {
+                invoked_programs.push(invoked_program);
+            } else if let Some((program, result)) = program.split_once(' ') {
+                if invoked_programs.last() == Some(&program)
+                    && (result == "success" || result.starts_with("failed"))
+                {
+                    invoked_programs.pop();
+                }
+            }
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_logs() {
+        let program_id = Pubkey::new_unique();
+        let vote_account = Pubkey::new_unique();
+        let withdraw_event = RewardsVaultEvent::WithdrawRewards {
+            vote_account,
+            rewards_recipient: Pubkey::new_unique(),
+            lamports: 12345678,
+            epoch: 42,
+        };
+        let leave_event = RewardsVaultEvent::Leave {
+            vote_account,
+            withdraw_authority: Pubkey::new_unique(),
+            epoch: 43,
+        };
+        assert_eq!(
+            RewardsVaultEvent::unpack(&withdraw_event.pack()),
+            Some(withdraw_event)
+        );
+        assert_eq!(RewardsVaultEvent::unpack(&leave_event.pack()[..40]), None);
+        let split_event = RewardsVaultEvent::SetSplit {
+            vote_account,
+            split_recipients: [
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::default(),
+                Pubkey::default(),
+            ],
+            split_bps: [9_000, 1_000, 0, 0],
+            epoch: 42,
+        };
+        assert_eq!(
+            RewardsVaultEvent::unpack(&split_event.pack()),
+            Some(split_event)
+        );
+
+        let crank_mode_event = RewardsVaultEvent::SetCrankMode {
+            vote_account,
+            crank_enabled: true,
+            epoch: 42,
+        };
+        let mut crank_mode_data = crank_mode_event.pack();
+        assert_eq!(crank_mode_data.len(), 1 + 32 + 1 + 8);
+        assert_eq!(
+            RewardsVaultEvent::unpack(&crank_mode_data),
+            Some(crank_mode_event)
+        );
+        crank_mode_data[33] = 2;
+        assert_eq!(RewardsVaultEvent::unpack(&crank_mode_data), None);
+
+        let other_program_id = Pubkey::new_unique();
+        let log_data = |event: &RewardsVaultEvent| {
+            format!("{}{}", LOG_DATA_PREFIX, base64::encode(event.pack()))
+        };
+        let log_messages = [
+            format!("Program {} invoke [1]", program_id),
+            "Program log: Instruction: WithdrawRewards".to_string(),
+            format!("Program {} invoke [2]", other_program_id),
+            log_data(&leave_event),
+            format!("Program {} success", other_program_id),
+            log_data(&withdraw_event),
+            format!("Program {} success", program_id),
+            format!("Program {} invoke [1]", other_program_id),
+            log_data(&leave_event),
+            format!("Program {} success", other_program_id),
+            format!("Program {} invoke [1]", program_id),
+            log_data(&leave_event),
+            format!("Program {} success", program_id),
+        ];
+
+        assert_eq!(
+            parse_logs(&program_id, &log_messages),
+            vec![withdraw_event, leave_event]
+        );
+        assert_eq!(
+            parse_logs(&other_program_id, &log_messages),
+            vec![leave_event, leave_event]
+        );
+    }
+}
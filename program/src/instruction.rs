@@ -1,10 +1,10 @@
 use {
-    crate::{get_rewards_vault_address, id},
+    crate::get_rewards_vault_address,
     num_enum::{IntoPrimitive, TryFromPrimitive},
     solana_program::{
         instruction::{AccountMeta, Instruction},
         pubkey::Pubkey,
-        system_program, sysvar, vote,
+        stake, system_program, sysvar, vote,
     },
 };
 
@@ -28,6 +28,9 @@ pub enum RewardsVaultInstruction {
     ///   6. `[]` Vote program id
     ///   7. `[]` Clock sysvar
     ///
+    /// Instruction data: an optional little-endian `u64` number of lamports to retain in the vote
+    /// account as a reserve, see `RewardsVaultInstruction::SetReserve`
+    ///
     Enter,
 
     /// Remove the vote account from its rewards vault.
@@ -46,26 +49,273 @@ pub enum RewardsVaultInstruction {
 
     /// Withdraw epoch rewards earned to date by a vote account while it is resides in the rewards vault.
     ///
-    ///   0. `[]` Initialized vault account, generated by `get_rewards_vault_address`
+    ///   0. `[writable]` Initialized vault account, generated by `get_rewards_vault_address`. May be
+    ///      read-only for a legacy version 0 vault, which records no withdrawals
     ///   1. `[writable]` Vote account
     ///   2. `[writable]` Recipient account for the epoch rewards
     ///   3. `[signer]` Rewards authority
     ///   4. `[]` Vote program id
     ///
+    /// Instruction data: an optional little-endian `u64` number of lamports to withdraw. If
+    /// omitted, all withdrawable lamports are withdrawn subject to the per-epoch withdraw limit
+    ///
     WithdrawRewards,
+
+    /// Install a new rewards authority for a vote account residing in the rewards vault.
+    ///
+    /// Intended to regain control should the current rewards authority be compromised, without
+    /// requiring the vote account to leave the rewards vault.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Initialized vault account, generated by `get_rewards_vault_address`
+    ///   1. `[]` Vote account
+    ///   2. `[signer]` Vote account withdraw authority provided to `RewardsVaultInstruction::Enter`
+    ///   3. `[]` New rewards authority
+    ///
+    SetRewardsAuthority,
+
+    /// Nominate a new withdraw authority to replace the withdraw authority provided to
+    /// `RewardsVaultInstruction::Enter`.
+    ///
+    /// The nomination takes effect only once the nominee signs a
+    /// `RewardsVaultInstruction::AcceptWithdrawAuthority` instruction. Any previous nomination is
+    /// replaced.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Initialized vault account, generated by `get_rewards_vault_address`
+    ///   1. `[]` Vote account
+    ///   2. `[signer]` Current vault withdraw authority
+    ///   3. `[]` Nominated withdraw authority
+    ///
+    ProposeWithdrawAuthority,
+
+    /// Accept a nomination made by `RewardsVaultInstruction::ProposeWithdrawAuthority`, replacing
+    /// the current vault withdraw authority.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Initialized vault account, generated by `get_rewards_vault_address`
+    ///   1. `[]` Vote account
+    ///   2. `[signer]` Nominated withdraw authority
+    ///
+    AcceptWithdrawAuthority,
+
+    /// Cancel a pending nomination made by `RewardsVaultInstruction::ProposeWithdrawAuthority`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Initialized vault account, generated by `get_rewards_vault_address`
+    ///   1. `[]` Vote account
+    ///   2. `[signer]` Current vault withdraw authority
+    ///
+    CancelWithdrawAuthority,
+
+    /// Update the commission of a vote account residing in the rewards vault.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Initialized vault account, generated by `get_rewards_vault_address`
+    ///   1. `[writable]` Vote account
+    ///   2. `[signer]` Vault withdraw authority
+    ///   3. `[]` Vote program id
+    ///
+    /// Instruction data: the new commission percentage as a `u8`, at most 100
+    ///
+    UpdateCommission,
+
+    /// Update the validator identity of a vote account residing in the rewards vault.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Initialized vault account, generated by `get_rewards_vault_address`
+    ///   1. `[writable]` Vote account
+    ///   2. `[signer]` Vault withdraw authority
+    ///   3. `[signer]` New validator identity
+    ///   4. `[]` Vote program id
+    ///
+    UpdateValidatorIdentity,
+
+    /// Authorize a new voter for a vote account residing in the rewards vault.
+    ///
+    /// The vote program applies the new authorized voter from the epoch following the current
+    /// leader schedule epoch, and permits only one authorized voter change per epoch.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Initialized vault account, generated by `get_rewards_vault_address`
+    ///   1. `[writable]` Vote account
+    ///   2. `[signer]` Vault withdraw authority
+    ///   3. `[]` New authorized voter
+    ///   4. `[]` Vote program id
+    ///   5. `[]` Clock sysvar
+    ///
+    AuthorizeVoter,
+
+    /// Set the number of lamports retained in the vote account, in addition to its rent-exempt
+    /// minimum balance, that the rewards authority may never withdraw.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Initialized vault account, generated by `get_rewards_vault_address`
+    ///   1. `[]` Vote account
+    ///   2. `[signer]` Vault withdraw authority
+    ///
+    /// Instruction data: the reserve in lamports as a little-endian `u64`
+    ///
+    SetReserve,
+
+    /// Lock or unlock the account that epoch rewards may be withdrawn to.
+    ///
+    /// While locked, `RewardsVaultInstruction::WithdrawRewards` rejects any other rewards
+    /// recipient, so a compromised rewards authority is unable to redirect the epoch rewards.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Initialized vault account, generated by `get_rewards_vault_address`
+    ///   1. `[]` Vote account
+    ///   2. `[signer]` Vault withdraw authority
+    ///   3. `[]` Optional rewards recipient to lock. If omitted the rewards recipient is unlocked
+    ///
+    SetRewardsRecipient,
+
+    /// Enable or disable the permissionless `RewardsVaultInstruction::CrankRewards` instruction.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Initialized vault account, generated by `get_rewards_vault_address`
+    ///   1. `[]` Vote account
+    ///   2. `[signer]` Vault withdraw authority
+    ///
+    /// Instruction data: `1` to enable the crank, `0` to disable it
+    ///
+    SetCrankMode,
+
+    /// Withdraw all epoch rewards earned to date by a vote account residing in the rewards vault to
+    /// its locked rewards recipient.
+    ///
+    /// No signature is required, however the crank must be enabled with
+    /// `RewardsVaultInstruction::SetCrankMode` and the rewards recipient locked with
+    /// `RewardsVaultInstruction::SetRewardsRecipient`.
+    ///
+    ///   0. `[writable]` Initialized vault account, generated by `get_rewards_vault_address`. May be
+    ///      read-only for a legacy version 0 vault, which records no withdrawals
+    ///   1. `[writable]` Vote account
+    ///   2. `[writable]` Locked rewards recipient
+    ///   3. `[]` Vote program id
+    ///
+    CrankRewards,
+
+    /// Set the maximum number of lamports that may be withdrawn per epoch, bounding the damage
+    /// from a compromised rewards authority.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Initialized vault account, generated by `get_rewards_vault_address`
+    ///   1. `[]` Vote account
+    ///   2. `[signer]` Vault withdraw authority
+    ///
+    /// Instruction data: the limit in lamports as a little-endian `u64`, or 0 for no limit
+    ///
+    SetWithdrawLimit,
+
+    /// Configure the recipients `RewardsVaultInstruction::WithdrawRewardsSplit` splits epoch
+    /// rewards across.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Initialized vault account, generated by `get_rewards_vault_address`
+    ///   1. `[]` Vote account
+    ///   2. `[signer]` Vault withdraw authority
+    ///   3. `[]` Primary split recipient, also credited with any rounding dust
+    ///   4. ..3+N `[]` Up to `MAX_SPLIT_RECIPIENTS` - 1 additional split recipients
+    ///
+    /// Instruction data: the basis points of each split recipient as little-endian `u16`s, in
+    /// account order, totalling `SPLIT_TOTAL_BPS`. If no split recipients are provided the split is
+    /// cleared
+    ///
+    SetSplit,
+
+    /// Withdraw all epoch rewards earned to date by a vote account residing in the rewards vault,
+    /// splitting them across the recipients configured with `RewardsVaultInstruction::SetSplit`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Initialized vault account, generated by `get_rewards_vault_address`
+    ///   1. `[writable]` Vote account
+    ///   2. `[signer]` Rewards authority
+    ///   3. `[]` Vote program id
+    ///   4. ..4+N `[writable]` Split recipients, in the configured order
+    ///
+    WithdrawRewardsSplit,
+
+    /// Withdraw epoch rewards earned to date by a vote account residing in the rewards vault into
+    /// a new stake account delegated to the same vote account.
+    ///
+    /// The rewards authority becomes the stake authority of the new stake account. If the rewards
+    /// recipient is locked, the stake withdraw authority must be the locked rewards recipient.
+    ///
+    /// The withdrawn lamports, with any lamports already held by the new stake account, must cover
+    /// its rent-exempt reserve and the minimum stake delegation. If nothing is withdrawable the
+    /// instruction succeeds without creating the stake account.
+    ///
+    /// Existing stake accounts are not supported: lamports moved into a delegated stake account
+    /// are not delegated, and the stake program only merges the new stake account into an existing
+    /// one once both are active, so it may be merged by its stake authority in a later epoch.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Initialized vault account, generated by `get_rewards_vault_address`
+    ///   1. `[writable]` Vote account
+    ///   2. `[writable, signer]` New, uninitialized, stake account
+    ///   3. `[signer]` Rewards authority
+    ///   4. `[]` Withdraw authority of the new stake account
+    ///   5. `[]` Vote program id
+    ///   6. `[]` System program id
+    ///   7. `[]` Stake program id
+    ///   8. `[]` Rent sysvar
+    ///   9. `[]` Clock sysvar
+    ///  10. `[]` Stake history sysvar
+    ///  11. `[]` Stake config account
+    ///
+    /// Instruction data: an optional little-endian `u64` number of lamports to withdraw. If
+    /// omitted, all withdrawable lamports are withdrawn subject to the per-epoch withdraw limit
+    ///
+    WithdrawAndStake,
+
+    /// Migrate a vault account of a previous layout version to the current layout, reallocating
+    /// it. Vaults that are already current are left untouched.
+    ///
+    /// No authority signs: migration preserves every field of the vault, only storing the vault
+    /// address bump seed and zeroing the fields the previous layout lacks, and the funding account
+    /// pays for the larger account, so anybody may migrate any vault.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Initialized vault account, generated by `get_rewards_vault_address`
+    ///   1. `[]` Vote account
+    ///   2. `[writable, signer]` Funding account to cover the rent of the enlarged vault account
+    ///   3. `[]` System program id
+    ///
+    Migrate,
 }
 
 /// Creates a `RewardsVaultInstruction::Enter` instruction
 pub fn enter(
+    program_id: Pubkey,
     vote_account_address: Pubkey,
     funding_address: Pubkey,
     withdraw_authority: Pubkey,
     rewards_authority: Pubkey,
 ) -> Instruction {
     Instruction {
-        program_id: id(),
+        program_id,
         accounts: vec![
-            AccountMeta::new(get_rewards_vault_address(&vote_account_address), false),
+            AccountMeta::new(
+                get_rewards_vault_address(&program_id, &vote_account_address),
+                false,
+            ),
             AccountMeta::new(vote_account_address, false),
             AccountMeta::new(funding_address, true),
             AccountMeta::new_readonly(withdraw_authority, true),
@@ -78,16 +328,43 @@ pub fn enter(
     }
 }
 
+/// Creates a `RewardsVaultInstruction::Enter` instruction that retains `reserve_lamports` in the
+/// vote account
+pub fn enter_with_reserve(
+    program_id: Pubkey,
+    vote_account_address: Pubkey,
+    funding_address: Pubkey,
+    withdraw_authority: Pubkey,
+    rewards_authority: Pubkey,
+    reserve_lamports: u64,
+) -> Instruction {
+    let mut instruction = enter(
+        program_id,
+        vote_account_address,
+        funding_address,
+        withdraw_authority,
+        rewards_authority,
+    );
+    instruction
+        .data
+        .extend_from_slice(&reserve_lamports.to_le_bytes());
+    instruction
+}
+
 /// Creates a `RewardsVaultInstruction::Leave` instruction
 pub fn leave(
+    program_id: Pubkey,
     vote_account_address: Pubkey,
     refund_address: Pubkey,
     withdraw_authority: Pubkey,
 ) -> Instruction {
     Instruction {
-        program_id: id(),
+        program_id,
         accounts: vec![
-            AccountMeta::new(get_rewards_vault_address(&vote_account_address), false),
+            AccountMeta::new(
+                get_rewards_vault_address(&program_id, &vote_account_address),
+                false,
+            ),
             AccountMeta::new(vote_account_address, false),
             AccountMeta::new(refund_address, false),
             AccountMeta::new_readonly(withdraw_authority, true),
@@ -100,14 +377,18 @@ pub fn leave(
 
 /// Creates a `RewardsVaultInstruction::WithdrawRewards` instruction
 pub fn withdraw_rewards(
+    program_id: Pubkey,
     vote_account_address: Pubkey,
     rewards_recipient_address: Pubkey,
     rewards_authority: Pubkey,
 ) -> Instruction {
     Instruction {
-        program_id: id(),
+        program_id,
         accounts: vec![
-            AccountMeta::new(get_rewards_vault_address(&vote_account_address), false),
+            AccountMeta::new(
+                get_rewards_vault_address(&program_id, &vote_account_address),
+                false,
+            ),
             AccountMeta::new(vote_account_address, false),
             AccountMeta::new(rewards_recipient_address, false),
             AccountMeta::new_readonly(rewards_authority, true),
@@ -116,3 +397,424 @@ pub fn withdraw_rewards(
         data: vec![RewardsVaultInstruction::WithdrawRewards.into()],
     }
 }
+
+/// Creates a `RewardsVaultInstruction::WithdrawRewards` instruction that withdraws only `lamports`
+pub fn withdraw_rewards_with_amount(
+    program_id: Pubkey,
+    vote_account_address: Pubkey,
+    rewards_recipient_address: Pubkey,
+    rewards_authority: Pubkey,
+    lamports: u64,
+) -> Instruction {
+    let mut instruction = withdraw_rewards(
+        program_id,
+        vote_account_address,
+        rewards_recipient_address,
+        rewards_authority,
+    );
+    instruction.data.extend_from_slice(&lamports.to_le_bytes());
+    instruction
+}
+
+/// Creates a `RewardsVaultInstruction::SetRewardsAuthority` instruction
+pub fn set_rewards_authority(
+    program_id: Pubkey,
+    vote_account_address: Pubkey,
+    withdraw_authority: Pubkey,
+    new_rewards_authority: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(
+                get_rewards_vault_address(&program_id, &vote_account_address),
+                false,
+            ),
+            AccountMeta::new_readonly(vote_account_address, false),
+            AccountMeta::new_readonly(withdraw_authority, true),
+            AccountMeta::new_readonly(new_rewards_authority, false),
+        ],
+        data: vec![RewardsVaultInstruction::SetRewardsAuthority.into()],
+    }
+}
+
+/// Creates a `RewardsVaultInstruction::ProposeWithdrawAuthority` instruction
+pub fn propose_withdraw_authority(
+    program_id: Pubkey,
+    vote_account_address: Pubkey,
+    withdraw_authority: Pubkey,
+    new_withdraw_authority: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(
+                get_rewards_vault_address(&program_id, &vote_account_address),
+                false,
+            ),
+            AccountMeta::new_readonly(vote_account_address, false),
+            AccountMeta::new_readonly(withdraw_authority, true),
+            AccountMeta::new_readonly(new_withdraw_authority, false),
+        ],
+        data: vec![RewardsVaultInstruction::ProposeWithdrawAuthority.into()],
+    }
+}
+
+/// Creates a `RewardsVaultInstruction::AcceptWithdrawAuthority` instruction
+pub fn accept_withdraw_authority(
+    program_id: Pubkey,
+    vote_account_address: Pubkey,
+    new_withdraw_authority: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(
+                get_rewards_vault_address(&program_id, &vote_account_address),
+                false,
+            ),
+            AccountMeta::new_readonly(vote_account_address, false),
+            AccountMeta::new_readonly(new_withdraw_authority, true),
+        ],
+        data: vec![RewardsVaultInstruction::AcceptWithdrawAuthority.into()],
+    }
+}
+
+/// Creates a `RewardsVaultInstruction::CancelWithdrawAuthority` instruction
+pub fn cancel_withdraw_authority(
+    program_id: Pubkey,
+    vote_account_address: Pubkey,
+    withdraw_authority: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(
+                get_rewards_vault_address(&program_id, &vote_account_address),
+                false,
+            ),
+            AccountMeta::new_readonly(vote_account_address, false),
+            AccountMeta::new_readonly(withdraw_authority, true),
+        ],
+        data: vec![RewardsVaultInstruction::CancelWithdrawAuthority.into()],
+    }
+}
+
+/// Creates a `RewardsVaultInstruction::UpdateCommission` instruction
+pub fn update_commission(
+    program_id: Pubkey,
+    vote_account_address: Pubkey,
+    withdraw_authority: Pubkey,
+    commission: u8,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(
+                get_rewards_vault_address(&program_id, &vote_account_address),
+                false,
+            ),
+            AccountMeta::new(vote_account_address, false),
+            AccountMeta::new_readonly(withdraw_authority, true),
+            AccountMeta::new_readonly(vote::program::id(), false),
+        ],
+        data: vec![RewardsVaultInstruction::UpdateCommission.into(), commission],
+    }
+}
+
+/// Creates a `RewardsVaultInstruction::UpdateValidatorIdentity` instruction
+pub fn update_validator_identity(
+    program_id: Pubkey,
+    vote_account_address: Pubkey,
+    withdraw_authority: Pubkey,
+    new_identity: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(
+                get_rewards_vault_address(&program_id, &vote_account_address),
+                false,
+            ),
+            AccountMeta::new(vote_account_address, false),
+            AccountMeta::new_readonly(withdraw_authority, true),
+            AccountMeta::new_readonly(new_identity, true),
+            AccountMeta::new_readonly(vote::program::id(), false),
+        ],
+        data: vec![RewardsVaultInstruction::UpdateValidatorIdentity.into()],
+    }
+}
+
+/// Creates a `RewardsVaultInstruction::AuthorizeVoter` instruction
+pub fn authorize_voter(
+    program_id: Pubkey,
+    vote_account_address: Pubkey,
+    withdraw_authority: Pubkey,
+    new_authorized_voter: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(
+                get_rewards_vault_address(&program_id, &vote_account_address),
+                false,
+            ),
+            AccountMeta::new(vote_account_address, false),
+            AccountMeta::new_readonly(withdraw_authority, true),
+            AccountMeta::new_readonly(new_authorized_voter, false),
+            AccountMeta::new_readonly(vote::program::id(), false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: vec![RewardsVaultInstruction::AuthorizeVoter.into()],
+    }
+}
+
+/// Creates a `RewardsVaultInstruction::SetReserve` instruction
+pub fn set_reserve(
+    program_id: Pubkey,
+    vote_account_address: Pubkey,
+    withdraw_authority: Pubkey,
+    reserve_lamports: u64,
+) -> Instruction {
+    let mut data = vec![RewardsVaultInstruction::SetReserve.into()];
+    data.extend_from_slice(&reserve_lamports.to_le_bytes());
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(
+                get_rewards_vault_address(&program_id, &vote_account_address),
+                false,
+            ),
+            AccountMeta::new_readonly(vote_account_address, false),
+            AccountMeta::new_readonly(withdraw_authority, true),
+        ],
+        data,
+    }
+}
+
+/// Creates a `RewardsVaultInstruction::SetRewardsRecipient` instruction
+pub fn set_rewards_recipient(
+    program_id: Pubkey,
+    vote_account_address: Pubkey,
+    withdraw_authority: Pubkey,
+    rewards_recipient_address: Option<Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(
+            get_rewards_vault_address(&program_id, &vote_account_address),
+            false,
+        ),
+        AccountMeta::new_readonly(vote_account_address, false),
+        AccountMeta::new_readonly(withdraw_authority, true),
+    ];
+    if let Some(rewards_recipient_address) = rewards_recipient_address {
+        accounts.push(AccountMeta::new_readonly(rewards_recipient_address, false));
+    }
+
+    Instruction {
+        program_id,
+        accounts,
+        data: vec![RewardsVaultInstruction::SetRewardsRecipient.into()],
+    }
+}
+
+/// Creates a `RewardsVaultInstruction::SetCrankMode` instruction
+pub fn set_crank_mode(
+    program_id: Pubkey,
+    vote_account_address: Pubkey,
+    withdraw_authority: Pubkey,
+    crank_enabled: bool,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(
+                get_rewards_vault_address(&program_id, &vote_account_address),
+                false,
+            ),
+            AccountMeta::new_readonly(vote_account_address, false),
+            AccountMeta::new_readonly(withdraw_authority, true),
+        ],
+        data: vec![
+            RewardsVaultInstruction::SetCrankMode.into(),
+            crank_enabled.into(),
+        ],
+    }
+}
+
+/// Creates a `RewardsVaultInstruction::CrankRewards` instruction
+pub fn crank_rewards(
+    program_id: Pubkey,
+    vote_account_address: Pubkey,
+    locked_rewards_recipient_address: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(
+                get_rewards_vault_address(&program_id, &vote_account_address),
+                false,
+            ),
+            AccountMeta::new(vote_account_address, false),
+            AccountMeta::new(locked_rewards_recipient_address, false),
+            AccountMeta::new_readonly(vote::program::id(), false),
+        ],
+        data: vec![RewardsVaultInstruction::CrankRewards.into()],
+    }
+}
+
+/// Creates a `RewardsVaultInstruction::SetWithdrawLimit` instruction
+pub fn set_withdraw_limit(
+    program_id: Pubkey,
+    vote_account_address: Pubkey,
+    withdraw_authority: Pubkey,
+    max_withdraw_per_epoch: u64,
+) -> Instruction {
+    let mut data = vec![RewardsVaultInstruction::SetWithdrawLimit.into()];
+    data.extend_from_slice(&max_withdraw_per_epoch.to_le_bytes());
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(
+                get_rewards_vault_address(&program_id, &vote_account_address),
+                false,
+            ),
+            AccountMeta::new_readonly(vote_account_address, false),
+            AccountMeta::new_readonly(withdraw_authority, true),
+        ],
+        data,
+    }
+}
+
+/// Creates a `RewardsVaultInstruction::SetSplit` instruction. `split` lists each recipient and its
+/// basis points, primary recipient first
+pub fn set_split(
+    program_id: Pubkey,
+    vote_account_address: Pubkey,
+    withdraw_authority: Pubkey,
+    split: &[(Pubkey, u16)],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(
+            get_rewards_vault_address(&program_id, &vote_account_address),
+            false,
+        ),
+        AccountMeta::new_readonly(vote_account_address, false),
+        AccountMeta::new_readonly(withdraw_authority, true),
+    ];
+    let mut data = vec![RewardsVaultInstruction::SetSplit.into()];
+    for (split_recipient, bps) in split {
+        accounts.push(AccountMeta::new_readonly(*split_recipient, false));
+        data.extend_from_slice(&bps.to_le_bytes());
+    }
+
+    Instruction {
+        program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `RewardsVaultInstruction::WithdrawRewardsSplit` instruction
+pub fn withdraw_rewards_split(
+    program_id: Pubkey,
+    vote_account_address: Pubkey,
+    rewards_authority: Pubkey,
+    split_recipients: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(
+            get_rewards_vault_address(&program_id, &vote_account_address),
+            false,
+        ),
+        AccountMeta::new(vote_account_address, false),
+        AccountMeta::new_readonly(rewards_authority, true),
+        AccountMeta::new_readonly(vote::program::id(), false),
+    ];
+    accounts.extend(
+        split_recipients
+            .iter()
+            .map(|split_recipient| AccountMeta::new(*split_recipient, false)),
+    );
+
+    Instruction {
+        program_id,
+        accounts,
+        data: vec![RewardsVaultInstruction::WithdrawRewardsSplit.into()],
+    }
+}
+
+/// Creates a `RewardsVaultInstruction::WithdrawAndStake` instruction
+pub fn withdraw_and_stake(
+    program_id: Pubkey,
+    vote_account_address: Pubkey,
+    stake_account_address: Pubkey,
+    rewards_authority: Pubkey,
+    stake_withdraw_authority: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(
+                get_rewards_vault_address(&program_id, &vote_account_address),
+                false,
+            ),
+            AccountMeta::new(vote_account_address, false),
+            AccountMeta::new(stake_account_address, true),
+            AccountMeta::new_readonly(rewards_authority, true),
+            AccountMeta::new_readonly(stake_withdraw_authority, false),
+            AccountMeta::new_readonly(vote::program::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(stake::program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(sysvar::stake_history::id(), false),
+            AccountMeta::new_readonly(stake::config::id(), false),
+        ],
+        data: vec![RewardsVaultInstruction::WithdrawAndStake.into()],
+    }
+}
+
+/// Creates a `RewardsVaultInstruction::WithdrawAndStake` instruction that withdraws a specific
+/// number of lamports
+pub fn withdraw_and_stake_with_amount(
+    program_id: Pubkey,
+    vote_account_address: Pubkey,
+    stake_account_address: Pubkey,
+    rewards_authority: Pubkey,
+    stake_withdraw_authority: Pubkey,
+    lamports: u64,
+) -> Instruction {
+    let mut instruction = withdraw_and_stake(
+        program_id,
+        vote_account_address,
+        stake_account_address,
+        rewards_authority,
+        stake_withdraw_authority,
+    );
+    instruction.data.extend_from_slice(&lamports.to_le_bytes());
+    instruction
+}
+
+/// Creates a `RewardsVaultInstruction::Migrate` instruction
+pub fn migrate(
+    program_id: Pubkey,
+    vote_account_address: Pubkey,
+    funding_address: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(
+                get_rewards_vault_address(&program_id, &vote_account_address),
+                false,
+            ),
+            AccountMeta::new_readonly(vote_account_address, false),
+            AccountMeta::new(funding_address, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: vec![RewardsVaultInstruction::Migrate.into()],
+    }
+}
@@ -1,29 +1,202 @@
 use {
     crate::{
-        create_pda_account::create_pda_account, get_rewards_vault_address_and_bump_seed, id,
-        instruction::RewardsVaultInstruction, state::RewardsVaultState,
+        accounts::{
+            check_writable, AcceptWithdrawAuthorityAccounts, AuthorizeVoterAccounts,
+            CrankRewardsAccounts, EnterAccounts, LeaveAccounts, MigrateAccounts,
+            SetAuthorityAccounts, SetRewardsRecipientAccounts, SetSplitAccounts,
+            SetVaultStateAccounts, UpdateCommissionAccounts, UpdateValidatorIdentityAccounts,
+            VaultAccounts, WithdrawAndStakeAccounts, WithdrawRewardsAccounts,
+            WithdrawRewardsSplitAccounts,
+        },
+        create_pda_account::create_pda_account,
+        create_rewards_vault_address,
+        error::RewardsVaultError,
+        event::RewardsVaultEvent,
+        get_rewards_vault_address_and_bump_seed,
+        instruction::RewardsVaultInstruction,
+        state::{RewardsVaultState, MAX_SPLIT_RECIPIENTS, SPLIT_TOTAL_BPS},
     },
     bytemuck::Zeroable,
     solana_program::{
-        account_info::{next_account_info, AccountInfo},
+        account_info::AccountInfo,
+        clock::Clock,
         entrypoint::ProgramResult,
         msg,
-        program::invoke_signed,
+        program::{invoke, invoke_signed},
         program_error::ProgramError,
         pubkey::Pubkey,
         rent::Rent,
+        stake::{
+            self,
+            state::{Authorized, Lockup, StakeState},
+        },
+        system_instruction, system_program,
         sysvar::Sysvar,
         vote::state::VoteAuthorize,
     },
 };
 
+fn unpack_optional_u64(data: &[u8]) -> Result<Option<u64>, ProgramError> {
+    match data.len() {
+        0 => Ok(None),
+        8 => Ok(Some(u64::from_le_bytes(data.try_into().unwrap()))),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn withdrawable_lamports(
+    vault_state: &RewardsVaultState,
+    vote_account_info: &AccountInfo,
+) -> Result<u64, ProgramError> {
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(vote_account_info.data_len());
+    if vote_account_info.lamports() < rent_exempt_minimum {
+        msg!(
+            "Vote account balance is {} lamports, below its rent-exempt minimum of {} lamports",
+            vote_account_info.lamports(),
+            rent_exempt_minimum
+        );
+        return Err(RewardsVaultError::VoteAccountBelowMinimumBalance.into());
+    }
+
+    // A balance within the reserve leaves nothing to withdraw
+    Ok(vote_account_info
+        .lamports()
+        .saturating_sub(rent_exempt_minimum.saturating_add(vault_state.reserve_lamports)))
+}
+
+/// Determines the lamports to withdraw from the vote account, enforcing the per-epoch withdraw
+/// limit, and records the withdrawal in the vault state and its lifetime accounting
+fn prepare_withdrawal(
+    vault_state: &mut RewardsVaultState,
+    vote_account_info: &AccountInfo,
+    requested_lamports: Option<u64>,
+) -> Result<u64, ProgramError> {
+    let withdrawable_lamports = withdrawable_lamports(vault_state, vote_account_info)?;
+
+    // The bookkeeping of an earlier epoch no longer counts towards the limit
+    let epoch = Clock::get()?.epoch;
+    let withdrawn_this_epoch = if vault_state.last_withdraw_epoch == epoch {
+        vault_state.withdrawn_this_epoch
+    } else {
+        0
+    };
+    let epoch_allowance = if vault_state.max_withdraw_per_epoch == 0 {
+        u64::MAX
+    } else {
+        vault_state
+            .max_withdraw_per_epoch
+            .saturating_sub(withdrawn_this_epoch)
+    };
+
+    let lamports = match requested_lamports {
+        Some(requested_lamports) if requested_lamports > withdrawable_lamports => {
+            msg!(
+                "Requested {} lamports but only {} lamports are withdrawable",
+                requested_lamports,
+                withdrawable_lamports
+            );
+            return Err(RewardsVaultError::InsufficientRewards.into());
+        }
+        Some(requested_lamports) if requested_lamports > epoch_allowance => {
+            msg!(
+                "Requested {} lamports but only {} lamports may be withdrawn this epoch",
+                requested_lamports,
+                epoch_allowance
+            );
+            return Err(RewardsVaultError::WithdrawLimitExceeded.into());
+        }
+        Some(requested_lamports) => requested_lamports,
+        None if withdrawable_lamports > 0 && epoch_allowance == 0 => {
+            msg!("Withdraw limit for epoch {} reached", epoch);
+            return Err(RewardsVaultError::WithdrawLimitExceeded.into());
+        }
+        None => withdrawable_lamports.min(epoch_allowance),
+    };
+
+    // Only an actual withdrawal is recorded, so that a permissionless crank with nothing to
+    // withdraw leaves the vault state untouched
+    if lamports > 0 {
+        vault_state.last_withdraw_epoch = epoch;
+        vault_state.withdrawn_this_epoch = withdrawn_this_epoch.saturating_add(lamports);
+        vault_state.total_rewards_withdrawn =
+            vault_state.total_rewards_withdrawn.saturating_add(lamports);
+        vault_state.withdraw_count = vault_state.withdraw_count.saturating_add(1);
+    }
+    Ok(lamports)
+}
+
+/// Stores the vault state after `prepare_withdrawal`. A legacy vault holds neither withdraw
+/// bookkeeping nor lifetime accounting, so it is left untouched and may be provided read-only, as
+/// by clients predating the per-epoch withdraw limit
+fn pack_withdrawal(vault_state: &RewardsVaultState, vault_info: &AccountInfo) -> ProgramResult {
+    if vault_state.version == 0 {
+        return Ok(());
+    }
+    check_writable(vault_info)?;
+    vault_state.pack(&mut vault_info.try_borrow_mut_data()?)
+}
+
+fn withdraw_from_vote_account<'a>(
+    vault_info: &AccountInfo<'a>,
+    vote_account_info: &AccountInfo<'a>,
+    rewards_recipient_info: &AccountInfo<'a>,
+    vote_program_info: &AccountInfo<'a>,
+    lamports: u64,
+    vault_account_signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    msg!("Withdrawing {} lamports", lamports);
+
+    invoke_signed(
+        &solana_program::vote::instruction::withdraw(
+            vote_account_info.key,
+            vault_info.key,
+            lamports,
+            rewards_recipient_info.key,
+        ),
+        &[
+            vote_account_info.clone(),
+            vault_info.clone(),
+            rewards_recipient_info.clone(),
+            vote_program_info.clone(),
+        ],
+        &[vault_account_signer_seeds],
+    )?;
+
+    RewardsVaultEvent::WithdrawRewards {
+        vote_account: *vote_account_info.key,
+        rewards_recipient: *rewards_recipient_info.key,
+        lamports,
+        epoch: Clock::get()?.epoch,
+    }
+    .emit();
+    Ok(())
+}
+
+fn check_withdraw_authority(
+    vault_state: &RewardsVaultState,
+    withdraw_authority_info: &AccountInfo,
+) -> ProgramResult {
+    if vault_state.original_withdraw_authority != *withdraw_authority_info.key {
+        return Err(RewardsVaultError::WrongWithdrawAuthority.into());
+    }
+    Ok(())
+}
+
+fn check_rewards_authority(
+    vault_state: &RewardsVaultState,
+    rewards_authority_info: &AccountInfo,
+) -> ProgramResult {
+    if vault_state.rewards_authority != *rewards_authority_info.key {
+        return Err(RewardsVaultError::WrongRewardsAuthority.into());
+    }
+    Ok(())
+}
+
 pub(crate) fn process_instruction(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    assert_eq!(*program_id, id());
-
     let instruction = RewardsVaultInstruction::try_from(
         *instruction_data
             .first()
@@ -34,16 +207,49 @@ pub(crate) fn process_instruction(
     msg!("Instruction: {:?}", instruction);
 
     let account_info_iter = &mut accounts.iter();
-    let vault_info = next_account_info(account_info_iter)?;
-    let vote_account_info = next_account_info(account_info_iter)?;
-
-    let vault_address_bump_seed = {
-        let (vault_address, vault_address_bump_seed) =
-            get_rewards_vault_address_and_bump_seed(vote_account_info.key);
-        if vault_address != *vault_info.key {
-            return Err(ProgramError::InvalidArgument);
+    let vault_accounts = VaultAccounts::parse(account_info_iter)?;
+    let vault_info = vault_accounts.vault_info;
+    let vote_account_info = vault_accounts.vote_account_info;
+
+    // Every instruction but Enter, which creates the vault state, reads the vault state unpacked
+    // once here
+    let mut vault_state = if instruction == RewardsVaultInstruction::Enter {
+        RewardsVaultState::zeroed()
+    } else {
+        if vault_info.owner != program_id {
+            return Err(RewardsVaultError::IncorrectVaultOwner.into());
+        }
+        RewardsVaultState::unpack(&vault_info.try_borrow_data()?)?
+    };
+
+    // Entered vaults record their bump seed, only a new or legacy vault requires the bump seed search
+    let stored_vault_address_bump_seed = if vault_state.version != 0 {
+        Some(vault_state.bump_seed)
+    } else {
+        None
+    };
+
+    let vault_address_bump_seed = match stored_vault_address_bump_seed {
+        Some(vault_address_bump_seed) => {
+            let vault_address = create_rewards_vault_address(
+                program_id,
+                vote_account_info.key,
+                vault_address_bump_seed,
+            )
+            .map_err(|_| RewardsVaultError::VaultAddressMismatch)?;
+            if vault_address != *vault_info.key {
+                return Err(RewardsVaultError::VaultAddressMismatch.into());
+            }
+            vault_address_bump_seed
+        }
+        None => {
+            let (vault_address, vault_address_bump_seed) =
+                get_rewards_vault_address_and_bump_seed(program_id, vote_account_info.key);
+            if vault_address != *vault_info.key {
+                return Err(RewardsVaultError::VaultAddressMismatch.into());
+            }
+            vault_address_bump_seed
         }
-        vault_address_bump_seed
     };
 
     let vault_account_signer_seeds: &[&[_]] = &[
@@ -54,18 +260,23 @@ pub(crate) fn process_instruction(
 
     match instruction {
         RewardsVaultInstruction::Enter => {
-            let funder_info = next_account_info(account_info_iter)?;
-            let withdraw_authority_info = next_account_info(account_info_iter)?;
-            let rewards_authority_info = next_account_info(account_info_iter)?;
-            let system_program_info = next_account_info(account_info_iter)?;
-            let vote_program_info = next_account_info(account_info_iter)?;
-            let clock_sysvar_info = next_account_info(account_info_iter)?;
+            let EnterAccounts {
+                funder_info,
+                withdraw_authority_info,
+                rewards_authority_info,
+                system_program_info,
+                vote_program_info,
+                clock_sysvar_info,
+            } = EnterAccounts::parse(&vault_accounts, account_info_iter)?;
+
+            let reserve_lamports = unpack_optional_u64(&instruction_data[1..])?.unwrap_or(0);
+            let clock = Clock::get()?;
 
             create_pda_account(
                 funder_info,
                 &Rent::get()?,
                 /*space = */ RewardsVaultState::size_of(),
-                &id(),
+                program_id,
                 system_program_info,
                 vault_info,
                 vault_account_signer_seeds,
@@ -73,18 +284,21 @@ pub(crate) fn process_instruction(
 
             {
                 let mut vault_data = vault_info.try_borrow_mut_data()?;
-                let vault_state =
-                    bytemuck::try_from_bytes_mut::<RewardsVaultState>(&mut vault_data)
-                        .map_err(|_| ProgramError::InvalidAccountData)?;
 
-                if *vault_state != RewardsVaultState::zeroed() {
-                    return Err(ProgramError::AccountAlreadyInitialized);
+                if vault_data.iter().any(|byte| *byte != 0) {
+                    return Err(RewardsVaultError::AlreadyInVault.into());
                 }
 
-                *vault_state = RewardsVaultState {
+                RewardsVaultState {
+                    bump_seed: vault_address_bump_seed,
                     original_withdraw_authority: *withdraw_authority_info.key,
                     rewards_authority: *rewards_authority_info.key,
-                };
+                    reserve_lamports,
+                    entered_epoch: clock.epoch,
+                    entered_slot: clock.slot,
+                    ..RewardsVaultState::zeroed()
+                }
+                .pack(&mut vault_data)?;
             }
 
             invoke_signed(
@@ -102,25 +316,32 @@ pub(crate) fn process_instruction(
                     clock_sysvar_info.clone(),
                 ],
                 &[vault_account_signer_seeds],
-            )
+            )?;
+
+            RewardsVaultEvent::Enter {
+                vote_account: *vote_account_info.key,
+                withdraw_authority: *withdraw_authority_info.key,
+                rewards_authority: *rewards_authority_info.key,
+                reserve_lamports,
+                epoch: clock.epoch,
+            }
+            .emit();
+            Ok(())
         }
         RewardsVaultInstruction::Leave => {
-            let refunder_info = next_account_info(account_info_iter)?;
-            let withdraw_authority_info = next_account_info(account_info_iter)?;
-            let vote_program_info = next_account_info(account_info_iter)?;
-            let clock_sysvar_info = next_account_info(account_info_iter)?;
+            let LeaveAccounts {
+                refunder_info,
+                withdraw_authority_info,
+                vote_program_info,
+                clock_sysvar_info,
+            } = LeaveAccounts::parse(&vault_accounts, account_info_iter)?;
 
             {
-                let mut vault_data = vault_info.try_borrow_mut_data()?;
-                let vault_state =
-                    bytemuck::try_from_bytes_mut::<RewardsVaultState>(&mut vault_data)
-                        .map_err(|_| ProgramError::InvalidAccountData)?;
-
                 if vault_state.original_withdraw_authority != *withdraw_authority_info.key {
-                    return Err(ProgramError::MissingRequiredSignature);
+                    return Err(RewardsVaultError::WrongWithdrawAuthority.into());
                 }
 
-                *vault_state = RewardsVaultState::zeroed();
+                vault_info.try_borrow_mut_data()?.fill(0);
             }
 
             invoke_signed(
@@ -145,183 +366,837 @@ pub(crate) fn process_instruction(
                 **vault_info.try_borrow_mut_lamports()? = 0;
             }
 
+            RewardsVaultEvent::Leave {
+                vote_account: *vote_account_info.key,
+                withdraw_authority: *withdraw_authority_info.key,
+                epoch: Clock::get()?.epoch,
+            }
+            .emit();
             Ok(())
         }
         RewardsVaultInstruction::WithdrawRewards => {
-            let rewards_recipient = next_account_info(account_info_iter)?;
-            let rewards_authority_info = next_account_info(account_info_iter)?;
-            let vote_program_info = next_account_info(account_info_iter)?;
+            let WithdrawRewardsAccounts {
+                rewards_recipient,
+                rewards_authority_info,
+                vote_program_info,
+            } = WithdrawRewardsAccounts::parse(&vault_accounts, account_info_iter)?;
 
-            {
-                let vault_data = vault_info.try_borrow_data()?;
-                let vault_state = bytemuck::try_from_bytes::<RewardsVaultState>(&vault_data)
-                    .map_err(|_| ProgramError::InvalidAccountData)?;
+            let requested_lamports = unpack_optional_u64(&instruction_data[1..])?;
+
+            let lamports = {
+                check_rewards_authority(&vault_state, rewards_authority_info)?;
 
-                if vault_state.rewards_authority != *rewards_authority_info.key
-                    || !rewards_authority_info.is_signer
+                if vault_state.locked_rewards_recipient != Pubkey::default()
+                    && vault_state.locked_rewards_recipient != *rewards_recipient.key
                 {
-                    return Err(ProgramError::MissingRequiredSignature);
+                    msg!(
+                        "Rewards recipient is locked to {}",
+                        vault_state.locked_rewards_recipient
+                    );
+                    return Err(RewardsVaultError::RewardsRecipientLocked.into());
                 }
+
+                let lamports =
+                    prepare_withdrawal(&mut vault_state, vote_account_info, requested_lamports)?;
+                pack_withdrawal(&vault_state, vault_info)?;
+                lamports
+            };
+            if lamports == 0 {
+                msg!("Nothing to withdraw");
+                return Ok(());
             }
 
-            let minimum_balance = Rent::get()?.minimum_balance(vote_account_info.data_len());
-            let lamports = vote_account_info
-                .lamports()
-                .checked_sub(minimum_balance)
-                .ok_or(ProgramError::InvalidInstructionData)?;
+            withdraw_from_vote_account(
+                vault_info,
+                vote_account_info,
+                rewards_recipient,
+                vote_program_info,
+                lamports,
+                vault_account_signer_seeds,
+            )
+        }
+        RewardsVaultInstruction::SetRewardsAuthority => {
+            let SetAuthorityAccounts {
+                withdraw_authority_info,
+                new_authority_info: new_rewards_authority_info,
+            } = SetAuthorityAccounts::parse(&vault_accounts, account_info_iter)?;
+
+            check_withdraw_authority(&vault_state, withdraw_authority_info)?;
+
+            msg!("New rewards authority: {}", new_rewards_authority_info.key);
+            vault_state.rewards_authority = *new_rewards_authority_info.key;
+            vault_state.pack(&mut vault_info.try_borrow_mut_data()?)?;
+
+            RewardsVaultEvent::SetRewardsAuthority {
+                vote_account: *vote_account_info.key,
+                rewards_authority: *new_rewards_authority_info.key,
+                epoch: Clock::get()?.epoch,
+            }
+            .emit();
+            Ok(())
+        }
+        RewardsVaultInstruction::ProposeWithdrawAuthority => {
+            let SetAuthorityAccounts {
+                withdraw_authority_info,
+                new_authority_info: new_withdraw_authority_info,
+            } = SetAuthorityAccounts::parse(&vault_accounts, account_info_iter)?;
+
+            check_withdraw_authority(&vault_state, withdraw_authority_info)?;
+
+            if *new_withdraw_authority_info.key == Pubkey::default() {
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            msg!(
+                "Proposed withdraw authority: {}",
+                new_withdraw_authority_info.key
+            );
+            vault_state.pending_withdraw_authority = *new_withdraw_authority_info.key;
+            vault_state.pack(&mut vault_info.try_borrow_mut_data()?)?;
+
+            RewardsVaultEvent::ProposeWithdrawAuthority {
+                vote_account: *vote_account_info.key,
+                pending_withdraw_authority: *new_withdraw_authority_info.key,
+                epoch: Clock::get()?.epoch,
+            }
+            .emit();
+            Ok(())
+        }
+        RewardsVaultInstruction::AcceptWithdrawAuthority => {
+            let AcceptWithdrawAuthorityAccounts {
+                new_withdraw_authority_info,
+            } = AcceptWithdrawAuthorityAccounts::parse(&vault_accounts, account_info_iter)?;
+
+            if vault_state.pending_withdraw_authority == Pubkey::default() {
+                return Err(RewardsVaultError::NoPendingWithdrawAuthority.into());
+            }
+            if vault_state.pending_withdraw_authority != *new_withdraw_authority_info.key {
+                return Err(RewardsVaultError::WrongPendingWithdrawAuthority.into());
+            }
+
+            msg!(
+                "New withdraw authority: {}",
+                new_withdraw_authority_info.key
+            );
+            vault_state.original_withdraw_authority = *new_withdraw_authority_info.key;
+            vault_state.pending_withdraw_authority = Pubkey::default();
+            vault_state.pack(&mut vault_info.try_borrow_mut_data()?)?;
+
+            RewardsVaultEvent::AcceptWithdrawAuthority {
+                vote_account: *vote_account_info.key,
+                withdraw_authority: *new_withdraw_authority_info.key,
+                epoch: Clock::get()?.epoch,
+            }
+            .emit();
+            Ok(())
+        }
+        RewardsVaultInstruction::CancelWithdrawAuthority => {
+            let SetVaultStateAccounts {
+                withdraw_authority_info,
+            } = SetVaultStateAccounts::parse(&vault_accounts, account_info_iter)?;
+
+            check_withdraw_authority(&vault_state, withdraw_authority_info)?;
+
+            if vault_state.pending_withdraw_authority == Pubkey::default() {
+                return Err(RewardsVaultError::NoPendingWithdrawAuthority.into());
+            }
+
+            vault_state.pending_withdraw_authority = Pubkey::default();
+            vault_state.pack(&mut vault_info.try_borrow_mut_data()?)?;
 
-            msg!("Withdrawing {} lamports", lamports);
+            RewardsVaultEvent::CancelWithdrawAuthority {
+                vote_account: *vote_account_info.key,
+                epoch: Clock::get()?.epoch,
+            }
+            .emit();
+            Ok(())
+        }
+        RewardsVaultInstruction::UpdateCommission => {
+            let UpdateCommissionAccounts {
+                withdraw_authority_info,
+                vote_program_info,
+            } = UpdateCommissionAccounts::parse(&vault_accounts, account_info_iter)?;
+
+            let commission = match instruction_data {
+                [_, commission] => *commission,
+                _ => return Err(ProgramError::InvalidInstructionData),
+            };
+            if commission > 100 {
+                msg!("Commission of {}% exceeds 100%", commission);
+                return Err(RewardsVaultError::InvalidCommission.into());
+            }
+
+            check_withdraw_authority(&vault_state, withdraw_authority_info)?;
+
+            msg!("New commission: {}%", commission);
 
             invoke_signed(
-                &solana_program::vote::instruction::withdraw(
+                &solana_program::vote::instruction::update_commission(
                     vote_account_info.key,
                     vault_info.key,
-                    lamports,
-                    rewards_recipient.key,
+                    commission,
                 ),
                 &[
                     vote_account_info.clone(),
                     vault_info.clone(),
-                    rewards_recipient.clone(),
                     vote_program_info.clone(),
                 ],
                 &[vault_account_signer_seeds],
-            )
+            )?;
+
+            RewardsVaultEvent::UpdateCommission {
+                vote_account: *vote_account_info.key,
+                commission,
+                epoch: Clock::get()?.epoch,
+            }
+            .emit();
+            Ok(())
         }
-    }
-}
+        RewardsVaultInstruction::UpdateValidatorIdentity => {
+            let UpdateValidatorIdentityAccounts {
+                withdraw_authority_info,
+                new_identity_info,
+                vote_program_info,
+            } = UpdateValidatorIdentityAccounts::parse(&vault_accounts, account_info_iter)?;
 
-#[cfg(test)]
-mod test {
-    use {
-        super::*,
-        assert_matches::*,
-        solana_program::{
-            hash::Hash,
-            instruction::AccountMeta,
-            system_instruction,
-            vote::{
-                self,
-                state::{VoteInit, VoteState},
-            },
-        },
-        solana_program_test::*,
-        solana_sdk::{
-            signature::{Keypair, Signer},
-            transaction::Transaction,
-        },
-        tokio::time::{sleep, Duration},
-    };
+            check_withdraw_authority(&vault_state, withdraw_authority_info)?;
 
-    async fn create_vote_account(
-        banks_client: &mut BanksClient,
-        payer: &Keypair,
-    ) -> (
-        /*vote_account_keypair*/ Keypair,
-        /*authorized_withdrawer_keypair*/ Keypair,
-    ) {
-        let node_pubkey_keypair = Keypair::new();
-        let vote_account_keypair = Keypair::new();
-        let authorized_withdrawer_keypair = Keypair::new();
+            msg!("New validator identity: {}", new_identity_info.key);
 
-        let rent = banks_client.get_rent().await.unwrap();
+            invoke_signed(
+                &solana_program::vote::instruction::update_validator_identity(
+                    vote_account_info.key,
+                    vault_info.key,
+                    new_identity_info.key,
+                ),
+                &[
+                    vote_account_info.clone(),
+                    new_identity_info.clone(),
+                    vault_info.clone(),
+                    vote_program_info.clone(),
+                ],
+                &[vault_account_signer_seeds],
+            )?;
 
-        let mut transaction = Transaction::new_with_payer(
-            &vote::instruction::create_account(
-                &payer.pubkey(),
-                &vote_account_keypair.pubkey(),
-                &VoteInit {
-                    node_pubkey: node_pubkey_keypair.pubkey(),
-                    authorized_voter: Pubkey::new_unique(),
-                    authorized_withdrawer: authorized_withdrawer_keypair.pubkey(),
-                    commission: 42,
-                },
-                rent.minimum_balance(VoteState::size_of()),
-            ),
-            Some(&payer.pubkey()),
-        );
-        transaction.sign(
-            &[&payer, &vote_account_keypair, &node_pubkey_keypair],
-            banks_client
-                .get_latest_blockhash()
-                .await
-                .expect("blockhash"),
-        );
-        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+            RewardsVaultEvent::UpdateValidatorIdentity {
+                vote_account: *vote_account_info.key,
+                validator_identity: *new_identity_info.key,
+                epoch: Clock::get()?.epoch,
+            }
+            .emit();
+            Ok(())
+        }
+        RewardsVaultInstruction::AuthorizeVoter => {
+            let AuthorizeVoterAccounts {
+                withdraw_authority_info,
+                new_authorized_voter_info,
+                vote_program_info,
+                clock_sysvar_info,
+            } = AuthorizeVoterAccounts::parse(&vault_accounts, account_info_iter)?;
 
-        (vote_account_keypair, authorized_withdrawer_keypair)
-    }
+            check_withdraw_authority(&vault_state, withdraw_authority_info)?;
 
-    async fn get_new_blockhash(banks_client: &mut BanksClient) -> Hash {
-        let current_blockhash = banks_client
-            .get_latest_blockhash()
-            .await
-            .expect("blockhash");
-        loop {
-            let new_blockhash = banks_client
-                .get_latest_blockhash()
-                .await
-                .expect("blockhash");
-            if new_blockhash != current_blockhash {
-                return new_blockhash;
+            msg!("New authorized voter: {}", new_authorized_voter_info.key);
+
+            invoke_signed(
+                &solana_program::vote::instruction::authorize(
+                    vote_account_info.key,
+                    vault_info.key,
+                    new_authorized_voter_info.key,
+                    VoteAuthorize::Voter,
+                ),
+                &[
+                    vote_account_info.clone(),
+                    clock_sysvar_info.clone(),
+                    vault_info.clone(),
+                    vote_program_info.clone(),
+                ],
+                &[vault_account_signer_seeds],
+            )?;
+
+            RewardsVaultEvent::AuthorizeVoter {
+                vote_account: *vote_account_info.key,
+                authorized_voter: *new_authorized_voter_info.key,
+                epoch: Clock::get()?.epoch,
             }
-            sleep(Duration::from_millis(00)).await;
+            .emit();
+            Ok(())
         }
-    }
+        RewardsVaultInstruction::SetReserve => {
+            let SetVaultStateAccounts {
+                withdraw_authority_info,
+            } = SetVaultStateAccounts::parse(&vault_accounts, account_info_iter)?;
 
-    #[tokio::test]
-    async fn test_enter() {
-        let (mut banks_client, payer, _recent_blockhash) = ProgramTest::new(
-            "sol_rewards_vault_program",
-            crate::id(),
-            processor!(process_instruction),
-        )
-        .start()
-        .await;
+            let reserve_lamports = unpack_optional_u64(&instruction_data[1..])?
+                .ok_or(ProgramError::InvalidInstructionData)?;
 
-        let (vote_account_keypair, authorized_withdrawer_keypair) =
-            create_vote_account(&mut banks_client, &payer).await;
+            check_withdraw_authority(&vault_state, withdraw_authority_info)?;
 
-        let invalid_authorized_withdrawer_keypair = Keypair::new();
-        let rewards_authority_keypair = Keypair::new();
+            msg!("New reserve: {} lamports", reserve_lamports);
+            vault_state.reserve_lamports = reserve_lamports;
+            vault_state.pack(&mut vault_info.try_borrow_mut_data()?)?;
 
-        // invalid authorized withdrawer
-        let mut transaction = Transaction::new_with_payer(
-            &[crate::instruction::enter(
-                vote_account_keypair.pubkey(),
-                payer.pubkey(),
-                invalid_authorized_withdrawer_keypair.pubkey(),
-                rewards_authority_keypair.pubkey(),
-            )],
-            Some(&payer.pubkey()),
-        );
-        transaction.sign(
-            &[&payer, &invalid_authorized_withdrawer_keypair],
-            get_new_blockhash(&mut banks_client).await,
-        );
-        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+            RewardsVaultEvent::SetReserve {
+                vote_account: *vote_account_info.key,
+                reserve_lamports,
+                epoch: Clock::get()?.epoch,
+            }
+            .emit();
+            Ok(())
+        }
+        RewardsVaultInstruction::SetRewardsRecipient => {
+            let SetRewardsRecipientAccounts {
+                withdraw_authority_info,
+                rewards_recipient_info,
+            } = SetRewardsRecipientAccounts::parse(&vault_accounts, account_info_iter)?;
 
-        // invalid vault address
-        let mut instruction = crate::instruction::enter(
-            vote_account_keypair.pubkey(),
-            payer.pubkey(),
-            authorized_withdrawer_keypair.pubkey(),
-            rewards_authority_keypair.pubkey(),
-        );
-        instruction.accounts[0] = AccountMeta::new(
-            crate::get_rewards_vault_address(&Pubkey::new_unique()),
-            false,
-        );
-        let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
-        transaction.sign(
-            &[&payer, &authorized_withdrawer_keypair],
-            get_new_blockhash(&mut banks_client).await,
-        );
-        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+            check_withdraw_authority(&vault_state, withdraw_authority_info)?;
 
-        // enter ok
+            vault_state.locked_rewards_recipient = match rewards_recipient_info {
+                Some(rewards_recipient_info) => {
+                    msg!("Rewards recipient locked to {}", rewards_recipient_info.key);
+                    *rewards_recipient_info.key
+                }
+                None => {
+                    msg!("Rewards recipient unlocked");
+                    Pubkey::default()
+                }
+            };
+            vault_state.pack(&mut vault_info.try_borrow_mut_data()?)?;
+
+            RewardsVaultEvent::SetRewardsRecipient {
+                vote_account: *vote_account_info.key,
+                rewards_recipient: vault_state.locked_rewards_recipient,
+                epoch: Clock::get()?.epoch,
+            }
+            .emit();
+            Ok(())
+        }
+        RewardsVaultInstruction::SetCrankMode => {
+            let SetVaultStateAccounts {
+                withdraw_authority_info,
+            } = SetVaultStateAccounts::parse(&vault_accounts, account_info_iter)?;
+
+            let crank_enabled = match instruction_data {
+                [_, crank_enabled @ (0 | 1)] => *crank_enabled,
+                _ => return Err(ProgramError::InvalidInstructionData),
+            };
+
+            check_withdraw_authority(&vault_state, withdraw_authority_info)?;
+
+            msg!("Crank enabled: {}", crank_enabled != 0);
+            vault_state.crank_enabled = crank_enabled;
+            vault_state.pack(&mut vault_info.try_borrow_mut_data()?)?;
+
+            RewardsVaultEvent::SetCrankMode {
+                vote_account: *vote_account_info.key,
+                crank_enabled: crank_enabled != 0,
+                epoch: Clock::get()?.epoch,
+            }
+            .emit();
+            Ok(())
+        }
+        RewardsVaultInstruction::CrankRewards => {
+            let CrankRewardsAccounts {
+                rewards_recipient,
+                vote_program_info,
+            } = CrankRewardsAccounts::parse(&vault_accounts, account_info_iter)?;
+
+            let lamports = {
+                if vault_state.crank_enabled == 0 {
+                    return Err(RewardsVaultError::CrankNotEnabled.into());
+                }
+
+                if vault_state.locked_rewards_recipient == Pubkey::default() {
+                    return Err(RewardsVaultError::RewardsRecipientNotLocked.into());
+                }
+
+                if vault_state.locked_rewards_recipient != *rewards_recipient.key {
+                    msg!(
+                        "Rewards recipient is locked to {}",
+                        vault_state.locked_rewards_recipient
+                    );
+                    return Err(RewardsVaultError::RewardsRecipientLocked.into());
+                }
+
+                let lamports = prepare_withdrawal(&mut vault_state, vote_account_info, None)?;
+                pack_withdrawal(&vault_state, vault_info)?;
+                lamports
+            };
+            if lamports == 0 {
+                msg!("Nothing to withdraw");
+                return Ok(());
+            }
+
+            withdraw_from_vote_account(
+                vault_info,
+                vote_account_info,
+                rewards_recipient,
+                vote_program_info,
+                lamports,
+                vault_account_signer_seeds,
+            )
+        }
+        RewardsVaultInstruction::SetWithdrawLimit => {
+            let SetVaultStateAccounts {
+                withdraw_authority_info,
+            } = SetVaultStateAccounts::parse(&vault_accounts, account_info_iter)?;
+
+            let max_withdraw_per_epoch = unpack_optional_u64(&instruction_data[1..])?
+                .ok_or(ProgramError::InvalidInstructionData)?;
+
+            check_withdraw_authority(&vault_state, withdraw_authority_info)?;
+
+            msg!(
+                "New withdraw limit: {} lamports per epoch",
+                max_withdraw_per_epoch
+            );
+            vault_state.max_withdraw_per_epoch = max_withdraw_per_epoch;
+            vault_state.pack(&mut vault_info.try_borrow_mut_data()?)?;
+
+            RewardsVaultEvent::SetWithdrawLimit {
+                vote_account: *vote_account_info.key,
+                max_withdraw_per_epoch,
+                epoch: Clock::get()?.epoch,
+            }
+            .emit();
+            Ok(())
+        }
+        RewardsVaultInstruction::SetSplit => {
+            let SetSplitAccounts {
+                withdraw_authority_info,
+                split_recipient_infos,
+            } = SetSplitAccounts::parse(&vault_accounts, account_info_iter)?;
+
+            let split_data = &instruction_data[1..];
+            if split_recipient_infos.len() > MAX_SPLIT_RECIPIENTS
+                || split_data.len() != split_recipient_infos.len() * 2
+            {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            let split_bps = split_data
+                .chunks_exact(2)
+                .map(|bps| u16::from_le_bytes([bps[0], bps[1]]))
+                .collect::<Vec<_>>();
+
+            if !split_bps.is_empty()
+                && (split_bps.contains(&0)
+                    || split_bps.iter().map(|bps| u32::from(*bps)).sum::<u32>()
+                        != u32::from(SPLIT_TOTAL_BPS))
+            {
+                msg!(
+                    "Split basis points must be non-zero and total {}",
+                    SPLIT_TOTAL_BPS
+                );
+                return Err(RewardsVaultError::InvalidSplit.into());
+            }
+
+            check_withdraw_authority(&vault_state, withdraw_authority_info)?;
+
+            vault_state.split_recipients = [Pubkey::default(); MAX_SPLIT_RECIPIENTS];
+            vault_state.split_bps = [0; MAX_SPLIT_RECIPIENTS];
+            for (i, (split_recipient_info, bps)) in
+                split_recipient_infos.iter().zip(split_bps).enumerate()
+            {
+                msg!(
+                    "Split recipient {}: {} basis points",
+                    split_recipient_info.key,
+                    bps
+                );
+                vault_state.split_recipients[i] = *split_recipient_info.key;
+                vault_state.split_bps[i] = bps;
+            }
+            if split_recipient_infos.is_empty() {
+                msg!("Split cleared");
+            }
+            vault_state.pack(&mut vault_info.try_borrow_mut_data()?)?;
+
+            RewardsVaultEvent::SetSplit {
+                vote_account: *vote_account_info.key,
+                split_recipients: vault_state.split_recipients,
+                split_bps: vault_state.split_bps,
+                epoch: Clock::get()?.epoch,
+            }
+            .emit();
+            Ok(())
+        }
+        RewardsVaultInstruction::WithdrawRewardsSplit => {
+            let WithdrawRewardsSplitAccounts {
+                rewards_authority_info,
+                vote_program_info,
+                split_recipient_infos,
+            } = WithdrawRewardsSplitAccounts::parse(&vault_accounts, account_info_iter)?;
+
+            let (lamports, split) = {
+                check_rewards_authority(&vault_state, rewards_authority_info)?;
+
+                if vault_state.locked_rewards_recipient != Pubkey::default() {
+                    msg!(
+                        "Rewards recipient is locked to {}",
+                        vault_state.locked_rewards_recipient
+                    );
+                    return Err(RewardsVaultError::RewardsRecipientLocked.into());
+                }
+
+                let split = vault_state.split();
+                if split.is_empty() {
+                    return Err(RewardsVaultError::NoSplitConfigured.into());
+                }
+                if split_recipient_infos.len() != split.len()
+                    || split_recipient_infos.iter().zip(&split).any(
+                        |(split_recipient_info, (split_recipient, _))| {
+                            split_recipient_info.key != split_recipient
+                        },
+                    )
+                {
+                    return Err(RewardsVaultError::SplitRecipientMismatch.into());
+                }
+
+                let lamports = prepare_withdrawal(&mut vault_state, vote_account_info, None)?;
+                vault_state.pack(&mut vault_info.try_borrow_mut_data()?)?;
+                (lamports, split)
+            };
+            if lamports == 0 {
+                msg!("Nothing to withdraw");
+                return Ok(());
+            }
+
+            // Every recipient but the primary receives its share rounded down, the primary
+            // receives the remainder
+            let mut shares = split
+                .iter()
+                .map(|(_, bps)| {
+                    (u128::from(lamports) * u128::from(*bps) / u128::from(SPLIT_TOTAL_BPS)) as u64
+                })
+                .collect::<Vec<_>>();
+            shares[0] = lamports - shares[1..].iter().sum::<u64>();
+
+            for (split_recipient_info, share) in split_recipient_infos.iter().zip(shares) {
+                if share > 0 {
+                    withdraw_from_vote_account(
+                        vault_info,
+                        vote_account_info,
+                        split_recipient_info,
+                        vote_program_info,
+                        share,
+                        vault_account_signer_seeds,
+                    )?;
+                }
+            }
+            Ok(())
+        }
+        RewardsVaultInstruction::WithdrawAndStake => {
+            let WithdrawAndStakeAccounts {
+                stake_account_info,
+                rewards_authority_info,
+                stake_withdraw_authority_info,
+                vote_program_info,
+                system_program_info,
+                stake_program_info,
+                rent_sysvar_info,
+                clock_sysvar_info,
+                stake_history_sysvar_info,
+                stake_config_info,
+            } = WithdrawAndStakeAccounts::parse(&vault_accounts, account_info_iter)?;
+
+            let requested_lamports = unpack_optional_u64(&instruction_data[1..])?;
+
+            let lamports = {
+                check_rewards_authority(&vault_state, rewards_authority_info)?;
+
+                if vault_state.locked_rewards_recipient != Pubkey::default()
+                    && vault_state.locked_rewards_recipient != *stake_withdraw_authority_info.key
+                {
+                    msg!(
+                        "Rewards recipient is locked to {}",
+                        vault_state.locked_rewards_recipient
+                    );
+                    return Err(RewardsVaultError::RewardsRecipientLocked.into());
+                }
+
+                let lamports =
+                    prepare_withdrawal(&mut vault_state, vote_account_info, requested_lamports)?;
+                vault_state.pack(&mut vault_info.try_borrow_mut_data()?)?;
+                lamports
+            };
+            if lamports == 0 {
+                msg!("Nothing to withdraw");
+                return Ok(());
+            }
+
+            if *stake_account_info.owner != system_program::id()
+                || !stake_account_info.data_is_empty()
+            {
+                msg!("Stake account {} already exists", stake_account_info.key);
+                return Err(RewardsVaultError::StakeAccountInUse.into());
+            }
+
+            let minimum_stake_lamports = Rent::get()?
+                .minimum_balance(StakeState::size_of())
+                .saturating_add(stake::tools::get_minimum_delegation()?);
+            if stake_account_info.lamports().saturating_add(lamports) < minimum_stake_lamports {
+                msg!(
+                    "Staking {} lamports requires at least {} lamports",
+                    lamports,
+                    minimum_stake_lamports
+                );
+                return Err(RewardsVaultError::InsufficientStakeAmount.into());
+            }
+
+            withdraw_from_vote_account(
+                vault_info,
+                vote_account_info,
+                stake_account_info,
+                vote_program_info,
+                lamports,
+                vault_account_signer_seeds,
+            )?;
+
+            invoke(
+                &system_instruction::allocate(stake_account_info.key, StakeState::size_of() as u64),
+                &[stake_account_info.clone(), system_program_info.clone()],
+            )?;
+            invoke(
+                &system_instruction::assign(stake_account_info.key, &stake::program::id()),
+                &[stake_account_info.clone(), system_program_info.clone()],
+            )?;
+
+            msg!(
+                "Staking {} lamports in {}",
+                lamports,
+                stake_account_info.key
+            );
+            invoke(
+                &stake::instruction::initialize(
+                    stake_account_info.key,
+                    &Authorized {
+                        staker: *rewards_authority_info.key,
+                        withdrawer: *stake_withdraw_authority_info.key,
+                    },
+                    &Lockup::default(),
+                ),
+                &[
+                    stake_account_info.clone(),
+                    rent_sysvar_info.clone(),
+                    stake_program_info.clone(),
+                ],
+            )?;
+            invoke(
+                &stake::instruction::delegate_stake(
+                    stake_account_info.key,
+                    rewards_authority_info.key,
+                    vote_account_info.key,
+                ),
+                &[
+                    stake_account_info.clone(),
+                    vote_account_info.clone(),
+                    clock_sysvar_info.clone(),
+                    stake_history_sysvar_info.clone(),
+                    stake_config_info.clone(),
+                    rewards_authority_info.clone(),
+                    stake_program_info.clone(),
+                ],
+            )
+        }
+        RewardsVaultInstruction::Migrate => {
+            let MigrateAccounts {
+                funder_info,
+                system_program_info,
+            } = MigrateAccounts::parse(&vault_accounts, account_info_iter)?;
+
+            if vault_state.version == RewardsVaultState::VERSION {
+                msg!("Vault is already version {}", RewardsVaultState::VERSION);
+                return Ok(());
+            }
+
+            let required_lamports = Rent::get()?
+                .minimum_balance(RewardsVaultState::size_of())
+                .saturating_sub(vault_info.lamports());
+            if required_lamports > 0 {
+                invoke(
+                    &system_instruction::transfer(
+                        funder_info.key,
+                        vault_info.key,
+                        required_lamports,
+                    ),
+                    &[
+                        funder_info.clone(),
+                        vault_info.clone(),
+                        system_program_info.clone(),
+                    ],
+                )?;
+            }
+
+            vault_info.realloc(RewardsVaultState::size_of(), true)?;
+            RewardsVaultState {
+                bump_seed: vault_address_bump_seed,
+                ..vault_state
+            }
+            .pack(&mut vault_info.try_borrow_mut_data()?)?;
+
+            msg!(
+                "Migrated vault from version {} to version {}",
+                vault_state.version,
+                RewardsVaultState::VERSION
+            );
+
+            RewardsVaultEvent::Migrate {
+                vote_account: *vote_account_info.key,
+                previous_version: vault_state.version,
+                version: RewardsVaultState::VERSION,
+                epoch: Clock::get()?.epoch,
+            }
+            .emit();
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::state::RewardsVaultStateV0,
+        assert_matches::*,
+        solana_program::{
+            hash::Hash,
+            instruction::{AccountMeta, Instruction},
+            system_instruction, sysvar,
+            vote::{
+                self,
+                state::{VoteInit, VoteState},
+            },
+        },
+        solana_program_test::*,
+        solana_sdk::{
+            account::{Account, AccountSharedData},
+            instruction::InstructionError,
+            signature::{Keypair, Signer},
+            transaction::{Transaction, TransactionError},
+        },
+        tokio::time::{sleep, Duration},
+    };
+
+    async fn create_vote_account(
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+    ) -> (
+        /*vote_account_keypair*/ Keypair,
+        /*authorized_withdrawer_keypair*/ Keypair,
+    ) {
+        let node_pubkey_keypair = Keypair::new();
+        let vote_account_keypair = Keypair::new();
+        let authorized_withdrawer_keypair = Keypair::new();
+
+        let rent = banks_client.get_rent().await.unwrap();
+
+        let mut transaction = Transaction::new_with_payer(
+            &vote::instruction::create_account(
+                &payer.pubkey(),
+                &vote_account_keypair.pubkey(),
+                &VoteInit {
+                    node_pubkey: node_pubkey_keypair.pubkey(),
+                    authorized_voter: Pubkey::new_unique(),
+                    authorized_withdrawer: authorized_withdrawer_keypair.pubkey(),
+                    commission: 42,
+                },
+                rent.minimum_balance(VoteState::size_of()),
+            ),
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &vote_account_keypair, &node_pubkey_keypair],
+            banks_client
+                .get_latest_blockhash()
+                .await
+                .expect("blockhash"),
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        (vote_account_keypair, authorized_withdrawer_keypair)
+    }
+
+    async fn get_new_blockhash(banks_client: &mut BanksClient) -> Hash {
+        let current_blockhash = banks_client
+            .get_latest_blockhash()
+            .await
+            .expect("blockhash");
+        loop {
+            let new_blockhash = banks_client
+                .get_latest_blockhash()
+                .await
+                .expect("blockhash");
+            if new_blockhash != current_blockhash {
+                return new_blockhash;
+            }
+            sleep(Duration::from_millis(00)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_enter() {
+        let (mut banks_client, payer, _recent_blockhash) = ProgramTest::new(
+            "sol_rewards_vault_program",
+            crate::id(),
+            processor!(process_instruction),
+        )
+        .start()
+        .await;
+
+        let (vote_account_keypair, authorized_withdrawer_keypair) =
+            create_vote_account(&mut banks_client, &payer).await;
+
+        let invalid_authorized_withdrawer_keypair = Keypair::new();
+        let rewards_authority_keypair = Keypair::new();
+
+        // invalid authorized withdrawer
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::enter(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+                invalid_authorized_withdrawer_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &invalid_authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+
+        // invalid vault address
+        let mut instruction = crate::instruction::enter(
+            crate::id(),
+            vote_account_keypair.pubkey(),
+            payer.pubkey(),
+            authorized_withdrawer_keypair.pubkey(),
+            rewards_authority_keypair.pubkey(),
+        );
+        instruction.accounts[0] = AccountMeta::new(
+            crate::get_rewards_vault_address(&crate::id(), &Pubkey::new_unique()),
+            false,
+        );
+        let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(code))
+                if code == RewardsVaultError::VaultAddressMismatch as u32
+        );
+
+        // enter ok
         let mut transaction = Transaction::new_with_payer(
             &[crate::instruction::enter(
+                crate::id(),
                 vote_account_keypair.pubkey(),
                 payer.pubkey(),
                 authorized_withdrawer_keypair.pubkey(),
@@ -335,9 +1210,29 @@ mod test {
         );
         assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
 
+        // Bump seed is recorded
+        let vault_account = banks_client
+            .get_account(crate::get_rewards_vault_address(
+                &crate::id(),
+                &vote_account_keypair.pubkey(),
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+        let vault_state = RewardsVaultState::unpack(&vault_account.data).unwrap();
+        assert_eq!(
+            vault_state.bump_seed,
+            crate::get_rewards_vault_address_and_bump_seed(
+                &crate::id(),
+                &vote_account_keypair.pubkey()
+            )
+            .1
+        );
+
         // can't re-enter
         let mut transaction = Transaction::new_with_payer(
             &[crate::instruction::enter(
+                crate::id(),
                 vote_account_keypair.pubkey(),
                 payer.pubkey(),
                 authorized_withdrawer_keypair.pubkey(),
@@ -372,6 +1267,7 @@ mod test {
         // enter
         let mut transaction = Transaction::new_with_payer(
             &[crate::instruction::enter(
+                crate::id(),
                 vote_account_keypair.pubkey(),
                 payer.pubkey(),
                 authorized_withdrawer_keypair.pubkey(),
@@ -388,6 +1284,7 @@ mod test {
         // leave: invalid authorized withdrawer
         let mut transaction = Transaction::new_with_payer(
             &[crate::instruction::leave(
+                crate::id(),
                 vote_account_keypair.pubkey(),
                 payer.pubkey(),
                 invalid_authorized_withdrawer_keypair.pubkey(),
@@ -403,6 +1300,7 @@ mod test {
         // leave ok
         let mut transaction = Transaction::new_with_payer(
             &[crate::instruction::leave(
+                crate::id(),
                 vote_account_keypair.pubkey(),
                 refund_address,
                 authorized_withdrawer_keypair.pubkey(),
@@ -418,6 +1316,7 @@ mod test {
         assert_eq!(
             banks_client
                 .get_balance(crate::get_rewards_vault_address(
+                    &crate::id(),
                     &vote_account_keypair.pubkey()
                 ))
                 .await
@@ -456,6 +1355,7 @@ mod test {
         // Enter the rewards vault
         let mut transaction = Transaction::new_with_payer(
             &[crate::instruction::enter(
+                crate::id(),
                 vote_account_keypair.pubkey(),
                 payer.pubkey(),
                 authorized_withdrawer_keypair.pubkey(),
@@ -507,6 +1407,7 @@ mod test {
         // Reward authority can withdraw the rewards
         let mut transaction = Transaction::new_with_payer(
             &[crate::instruction::withdraw_rewards(
+                crate::id(),
                 vote_account_keypair.pubkey(),
                 rewards_recipient_address,
                 rewards_authority_keypair.pubkey(),
@@ -537,6 +1438,7 @@ mod test {
         // Leave the rewards vault
         let mut transaction = Transaction::new_with_payer(
             &[crate::instruction::leave(
+                crate::id(),
                 vote_account_keypair.pubkey(),
                 payer.pubkey(),
                 authorized_withdrawer_keypair.pubkey(),
@@ -584,4 +1486,3532 @@ mod test {
         );
         assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
     }
+
+    #[tokio::test]
+    async fn test_set_rewards_authority() {
+        let (mut banks_client, payer, _recent_blockhash) = ProgramTest::new(
+            "sol_rewards_vault_program",
+            crate::id(),
+            processor!(process_instruction),
+        )
+        .start()
+        .await;
+
+        let (vote_account_keypair, authorized_withdrawer_keypair) =
+            create_vote_account(&mut banks_client, &payer).await;
+
+        let rewards_authority_keypair = Keypair::new();
+        let new_rewards_authority_keypair = Keypair::new();
+        let epoch_rewards = 12345678;
+        let rewards_recipient_address = Pubkey::new_unique();
+
+        // Enter the rewards vault
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::enter(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Rewards authority cannot install a new rewards authority
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_rewards_authority(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+                new_rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(code))
+                if code == RewardsVaultError::WrongWithdrawAuthority as u32
+        );
+
+        // Withdraw authority installs a new rewards authority
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_rewards_authority(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                new_rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Simulate epoch rewards
+        let mut transaction = Transaction::new_with_payer(
+            &[system_instruction::transfer(
+                &payer.pubkey(),
+                &vote_account_keypair.pubkey(),
+                epoch_rewards,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], get_new_blockhash(&mut banks_client).await);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Previous rewards authority can no longer withdraw the rewards
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_recipient_address,
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(code))
+                if code == RewardsVaultError::WrongRewardsAuthority as u32
+        );
+
+        // New rewards authority can withdraw the rewards
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_recipient_address,
+                new_rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &new_rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        assert_eq!(
+            banks_client
+                .get_balance(rewards_recipient_address)
+                .await
+                .unwrap(),
+            epoch_rewards
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lifetime_accounting() {
+        let mut context = ProgramTest::new(
+            "sol_rewards_vault_program",
+            crate::id(),
+            processor!(process_instruction),
+        )
+        .start_with_context()
+        .await;
+        let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+
+        let (vote_account_keypair, authorized_withdrawer_keypair) =
+            create_vote_account(&mut context.banks_client, &payer).await;
+
+        let rewards_authority_keypair = Keypair::new();
+        let epoch_rewards = 12345678;
+        let rewards_recipient_address = Pubkey::new_unique();
+        let vault_address =
+            crate::get_rewards_vault_address(&crate::id(), &vote_account_keypair.pubkey());
+
+        // Enter the rewards vault, lock the rewards recipient and enable the crank
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                crate::instruction::enter(
+                    crate::id(),
+                    vote_account_keypair.pubkey(),
+                    payer.pubkey(),
+                    authorized_withdrawer_keypair.pubkey(),
+                    rewards_authority_keypair.pubkey(),
+                ),
+                crate::instruction::set_rewards_recipient(
+                    crate::id(),
+                    vote_account_keypair.pubkey(),
+                    authorized_withdrawer_keypair.pubkey(),
+                    Some(rewards_recipient_address),
+                ),
+                crate::instruction::set_crank_mode(
+                    crate::id(),
+                    vote_account_keypair.pubkey(),
+                    authorized_withdrawer_keypair.pubkey(),
+                    true,
+                ),
+            ],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+
+        let vault_state = RewardsVaultState::unpack(
+            &context
+                .banks_client
+                .get_account(vault_address)
+                .await
+                .unwrap()
+                .unwrap()
+                .data,
+        )
+        .unwrap();
+        let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+        assert_eq!(vault_state.total_rewards_withdrawn, 0);
+        assert_eq!(vault_state.withdraw_count, 0);
+        assert_eq!(vault_state.entered_epoch, clock.epoch);
+        assert_ne!(vault_state.entered_slot, 0);
+        assert!(vault_state.entered_slot <= clock.slot);
+
+        // Simulate epoch rewards
+        let mut transaction = Transaction::new_with_payer(
+            &[system_instruction::transfer(
+                &payer.pubkey(),
+                &vote_account_keypair.pubkey(),
+                epoch_rewards,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+
+        // The withdrawal is recorded, and withdrawing nothing afterwards records nothing
+        for _ in 0..2 {
+            let mut transaction = Transaction::new_with_payer(
+                &[crate::instruction::withdraw_rewards(
+                    crate::id(),
+                    vote_account_keypair.pubkey(),
+                    rewards_recipient_address,
+                    rewards_authority_keypair.pubkey(),
+                )],
+                Some(&payer.pubkey()),
+            );
+            transaction.sign(
+                &[&payer, &rewards_authority_keypair],
+                get_new_blockhash(&mut context.banks_client).await,
+            );
+            assert_matches!(
+                context.banks_client.process_transaction(transaction).await,
+                Ok(())
+            );
+
+            let vault_state = RewardsVaultState::unpack(
+                &context
+                    .banks_client
+                    .get_account(vault_address)
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .data,
+            )
+            .unwrap();
+            assert_eq!(vault_state.total_rewards_withdrawn, epoch_rewards);
+            assert_eq!(vault_state.withdraw_count, 1);
+            assert_eq!(vault_state.last_withdraw_epoch, clock.epoch);
+            assert_eq!(vault_state.withdrawn_this_epoch, epoch_rewards);
+        }
+
+        // Advance to the next epoch
+        let next_epoch_slot = context
+            .genesis_config()
+            .epoch_schedule
+            .get_first_slot_in_epoch(clock.epoch + 1);
+        context.warp_to_slot(next_epoch_slot).unwrap();
+
+        // Cranking nothing in a later epoch leaves the withdraw bookkeeping untouched
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::crank_rewards(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_recipient_address,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+
+        let vault_state = RewardsVaultState::unpack(
+            &context
+                .banks_client
+                .get_account(vault_address)
+                .await
+                .unwrap()
+                .unwrap()
+                .data,
+        )
+        .unwrap();
+        assert_eq!(vault_state.total_rewards_withdrawn, epoch_rewards);
+        assert_eq!(vault_state.withdraw_count, 1);
+        assert_eq!(vault_state.last_withdraw_epoch, clock.epoch);
+        assert_eq!(vault_state.withdrawn_this_epoch, epoch_rewards);
+    }
+
+    #[tokio::test]
+    async fn test_withdraw_authority_transfer() {
+        let (mut banks_client, payer, _recent_blockhash) = ProgramTest::new(
+            "sol_rewards_vault_program",
+            crate::id(),
+            processor!(process_instruction),
+        )
+        .start()
+        .await;
+
+        let (vote_account_keypair, authorized_withdrawer_keypair) =
+            create_vote_account(&mut banks_client, &payer).await;
+
+        let rewards_authority_keypair = Keypair::new();
+        let new_withdraw_authority_keypair = Keypair::new();
+        let invalid_withdraw_authority_keypair = Keypair::new();
+
+        // Enter the rewards vault
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::enter(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Nothing to accept yet
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::accept_withdraw_authority(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                new_withdraw_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &new_withdraw_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(code))
+                if code == RewardsVaultError::NoPendingWithdrawAuthority as u32
+        );
+
+        // Rewards authority cannot propose a new withdraw authority
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::propose_withdraw_authority(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+                new_withdraw_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+
+        // Withdraw authority proposes a new withdraw authority
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::propose_withdraw_authority(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                new_withdraw_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Only the nominee may accept
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::accept_withdraw_authority(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                invalid_withdraw_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &invalid_withdraw_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+
+        // Withdraw authority cancels the nomination
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::cancel_withdraw_authority(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Cancelled nomination cannot be accepted
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::accept_withdraw_authority(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                new_withdraw_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &new_withdraw_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+
+        // Propose again and accept
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                crate::instruction::propose_withdraw_authority(
+                    crate::id(),
+                    vote_account_keypair.pubkey(),
+                    authorized_withdrawer_keypair.pubkey(),
+                    new_withdraw_authority_keypair.pubkey(),
+                ),
+                crate::instruction::accept_withdraw_authority(
+                    crate::id(),
+                    vote_account_keypair.pubkey(),
+                    new_withdraw_authority_keypair.pubkey(),
+                ),
+            ],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[
+                &payer,
+                &authorized_withdrawer_keypair,
+                &new_withdraw_authority_keypair,
+            ],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        let vault_account = banks_client
+            .get_account(crate::get_rewards_vault_address(
+                &crate::id(),
+                &vote_account_keypair.pubkey(),
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+        let vault_state = bytemuck::from_bytes::<RewardsVaultState>(&vault_account.data);
+        assert_eq!(
+            vault_state.original_withdraw_authority,
+            new_withdraw_authority_keypair.pubkey()
+        );
+        assert_eq!(vault_state.pending_withdraw_authority, Pubkey::default());
+
+        // Previous withdraw authority can no longer leave
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::leave(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+
+        // New withdraw authority can leave
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::leave(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+                new_withdraw_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &new_withdraw_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_update_commission() {
+        let (mut banks_client, payer, _recent_blockhash) = ProgramTest::new(
+            "sol_rewards_vault_program",
+            crate::id(),
+            processor!(process_instruction),
+        )
+        .start()
+        .await;
+
+        let (vote_account_keypair, authorized_withdrawer_keypair) =
+            create_vote_account(&mut banks_client, &payer).await;
+
+        let rewards_authority_keypair = Keypair::new();
+        let invalid_authorized_withdrawer_keypair = Keypair::new();
+
+        // Enter the rewards vault
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::enter(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Withdraw authority can no longer update the commission directly
+        let mut transaction = Transaction::new_with_payer(
+            &[vote::instruction::update_commission(
+                &vote_account_keypair.pubkey(),
+                &authorized_withdrawer_keypair.pubkey(),
+                10,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+
+        // Invalid withdraw authority
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::update_commission(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                invalid_authorized_withdrawer_keypair.pubkey(),
+                10,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &invalid_authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+
+        // Rewards authority cannot update the commission
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::update_commission(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+                10,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+
+        // Commission above 100% is rejected
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::update_commission(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                101,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(code))
+                if code == RewardsVaultError::InvalidCommission as u32
+        );
+
+        // Update commission through the rewards vault
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::update_commission(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                10,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        let vote_account = banks_client
+            .get_account(vote_account_keypair.pubkey())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            VoteState::deserialize(&vote_account.data)
+                .unwrap()
+                .commission,
+            10
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_validator_identity() {
+        let (mut banks_client, payer, _recent_blockhash) = ProgramTest::new(
+            "sol_rewards_vault_program",
+            crate::id(),
+            processor!(process_instruction),
+        )
+        .start()
+        .await;
+
+        let (vote_account_keypair, authorized_withdrawer_keypair) =
+            create_vote_account(&mut banks_client, &payer).await;
+
+        let rewards_authority_keypair = Keypair::new();
+        let new_identity_keypair = Keypair::new();
+
+        // Enter the rewards vault
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::enter(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Rewards authority cannot update the validator identity
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::update_validator_identity(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+                new_identity_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair, &new_identity_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+
+        // New validator identity must sign
+        let mut instruction = crate::instruction::update_validator_identity(
+            crate::id(),
+            vote_account_keypair.pubkey(),
+            authorized_withdrawer_keypair.pubkey(),
+            new_identity_keypair.pubkey(),
+        );
+        instruction.accounts[3].is_signer = false;
+        let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+
+        // Update validator identity through the rewards vault
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::update_validator_identity(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                new_identity_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[
+                &payer,
+                &authorized_withdrawer_keypair,
+                &new_identity_keypair,
+            ],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        let vote_account = banks_client
+            .get_account(vote_account_keypair.pubkey())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            VoteState::deserialize(&vote_account.data)
+                .unwrap()
+                .node_pubkey,
+            new_identity_keypair.pubkey()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_authorize_voter() {
+        let mut context = ProgramTest::new(
+            "sol_rewards_vault_program",
+            crate::id(),
+            processor!(process_instruction),
+        )
+        .start_with_context()
+        .await;
+        let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+
+        let (vote_account_keypair, authorized_withdrawer_keypair) =
+            create_vote_account(&mut context.banks_client, &payer).await;
+
+        let rewards_authority_keypair = Keypair::new();
+        let new_authorized_voter = Pubkey::new_unique();
+        let next_authorized_voter = Pubkey::new_unique();
+
+        // Enter the rewards vault
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::enter(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+
+        // Rewards authority cannot authorize a new voter
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::authorize_voter(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+                new_authorized_voter,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Err(_)
+        );
+
+        // Authorize a new voter through the rewards vault
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::authorize_voter(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                new_authorized_voter,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+
+        // The new voter takes effect after the current leader schedule epoch
+        let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+        let target_epoch = clock.leader_schedule_epoch + 1;
+        let vote_state = VoteState::deserialize(
+            &context
+                .banks_client
+                .get_account(vote_account_keypair.pubkey())
+                .await
+                .unwrap()
+                .unwrap()
+                .data,
+        )
+        .unwrap();
+        assert_ne!(
+            vote_state
+                .authorized_voters()
+                .get_authorized_voter(clock.epoch),
+            Some(new_authorized_voter)
+        );
+        assert_eq!(
+            vote_state
+                .authorized_voters()
+                .get_authorized_voter(target_epoch),
+            Some(new_authorized_voter)
+        );
+
+        // Only one authorized voter change is permitted per epoch
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::authorize_voter(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                next_authorized_voter,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Err(_)
+        );
+
+        // Advance to the next epoch and authorize another voter
+        let next_epoch_slot = context
+            .genesis_config()
+            .epoch_schedule
+            .get_first_slot_in_epoch(clock.epoch + 1);
+        context.warp_to_slot(next_epoch_slot).unwrap();
+
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::authorize_voter(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                next_authorized_voter,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+
+        let vote_state = VoteState::deserialize(
+            &context
+                .banks_client
+                .get_account(vote_account_keypair.pubkey())
+                .await
+                .unwrap()
+                .unwrap()
+                .data,
+        )
+        .unwrap();
+        assert_eq!(
+            vote_state
+                .authorized_voters()
+                .get_authorized_voter(target_epoch),
+            Some(new_authorized_voter)
+        );
+        assert_eq!(
+            vote_state
+                .authorized_voters()
+                .get_authorized_voter(target_epoch + 1),
+            Some(next_authorized_voter)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_withdraw_amount() {
+        let (mut banks_client, payer, _recent_blockhash) = ProgramTest::new(
+            "sol_rewards_vault_program",
+            crate::id(),
+            processor!(process_instruction),
+        )
+        .start()
+        .await;
+
+        let (vote_account_keypair, authorized_withdrawer_keypair) =
+            create_vote_account(&mut banks_client, &payer).await;
+
+        let minimum_vote_account_balance = banks_client
+            .get_balance(vote_account_keypair.pubkey())
+            .await
+            .unwrap();
+        let rewards_authority_keypair = Keypair::new();
+        let epoch_rewards = 12345678;
+        let partial_rewards = 1234567;
+        let rewards_recipient_address = Pubkey::new_unique();
+
+        // Enter the rewards vault
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::enter(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Simulate epoch rewards
+        let mut transaction = Transaction::new_with_payer(
+            &[system_instruction::transfer(
+                &payer.pubkey(),
+                &vote_account_keypair.pubkey(),
+                epoch_rewards,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], get_new_blockhash(&mut banks_client).await);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Cannot withdraw more than the epoch rewards
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards_with_amount(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_recipient_address,
+                rewards_authority_keypair.pubkey(),
+                epoch_rewards + 1,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(code))
+                if code == RewardsVaultError::InsufficientRewards as u32
+        );
+
+        // Malformed amount
+        let mut instruction = crate::instruction::withdraw_rewards(
+            crate::id(),
+            vote_account_keypair.pubkey(),
+            rewards_recipient_address,
+            rewards_authority_keypair.pubkey(),
+        );
+        instruction.data.extend_from_slice(&[1, 2, 3]);
+        let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+
+        // Withdraw part of the epoch rewards
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards_with_amount(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_recipient_address,
+                rewards_authority_keypair.pubkey(),
+                partial_rewards,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        assert_eq!(
+            banks_client
+                .get_balance(rewards_recipient_address)
+                .await
+                .unwrap(),
+            partial_rewards
+        );
+        assert_eq!(
+            banks_client
+                .get_balance(vote_account_keypair.pubkey())
+                .await
+                .unwrap(),
+            minimum_vote_account_balance + epoch_rewards - partial_rewards
+        );
+
+        // Withdraw the remainder
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_recipient_address,
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        assert_eq!(
+            banks_client
+                .get_balance(rewards_recipient_address)
+                .await
+                .unwrap(),
+            epoch_rewards
+        );
+        assert_eq!(
+            banks_client
+                .get_balance(vote_account_keypair.pubkey())
+                .await
+                .unwrap(),
+            minimum_vote_account_balance
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reserve() {
+        let (mut banks_client, payer, _recent_blockhash) = ProgramTest::new(
+            "sol_rewards_vault_program",
+            crate::id(),
+            processor!(process_instruction),
+        )
+        .start()
+        .await;
+
+        let (vote_account_keypair, authorized_withdrawer_keypair) =
+            create_vote_account(&mut banks_client, &payer).await;
+
+        let minimum_vote_account_balance = banks_client
+            .get_balance(vote_account_keypair.pubkey())
+            .await
+            .unwrap();
+        let rewards_authority_keypair = Keypair::new();
+        let epoch_rewards = 12345678;
+        let reserve = 1000000;
+        let new_reserve = 2000000;
+        let rewards_recipient_address = Pubkey::new_unique();
+
+        // Enter the rewards vault with a reserve
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::enter_with_reserve(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+                reserve,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Simulate epoch rewards
+        let mut transaction = Transaction::new_with_payer(
+            &[system_instruction::transfer(
+                &payer.pubkey(),
+                &vote_account_keypair.pubkey(),
+                epoch_rewards,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], get_new_blockhash(&mut banks_client).await);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Reserve cannot be withdrawn
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards_with_amount(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_recipient_address,
+                rewards_authority_keypair.pubkey(),
+                epoch_rewards - reserve + 1,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+
+        // Withdraw everything above the reserve
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_recipient_address,
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        assert_eq!(
+            banks_client
+                .get_balance(rewards_recipient_address)
+                .await
+                .unwrap(),
+            epoch_rewards - reserve
+        );
+        assert_eq!(
+            banks_client
+                .get_balance(vote_account_keypair.pubkey())
+                .await
+                .unwrap(),
+            minimum_vote_account_balance + reserve
+        );
+
+        // Rewards authority cannot change the reserve
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_reserve(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+                0,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+
+        // Withdraw authority changes the reserve
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_reserve(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                new_reserve,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        let vault_account = banks_client
+            .get_account(crate::get_rewards_vault_address(
+                &crate::id(),
+                &vote_account_keypair.pubkey(),
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            bytemuck::from_bytes::<RewardsVaultState>(&vault_account.data).reserve_lamports,
+            new_reserve
+        );
+
+        // Simulate epoch rewards
+        let mut transaction = Transaction::new_with_payer(
+            &[system_instruction::transfer(
+                &payer.pubkey(),
+                &vote_account_keypair.pubkey(),
+                epoch_rewards,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], get_new_blockhash(&mut banks_client).await);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Withdraw everything above the new reserve
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_recipient_address,
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        assert_eq!(
+            banks_client
+                .get_balance(vote_account_keypair.pubkey())
+                .await
+                .unwrap(),
+            minimum_vote_account_balance + new_reserve
+        );
+    }
+
+    #[tokio::test]
+    async fn test_locked_rewards_recipient() {
+        let (mut banks_client, payer, _recent_blockhash) = ProgramTest::new(
+            "sol_rewards_vault_program",
+            crate::id(),
+            processor!(process_instruction),
+        )
+        .start()
+        .await;
+
+        let (vote_account_keypair, authorized_withdrawer_keypair) =
+            create_vote_account(&mut banks_client, &payer).await;
+
+        let rewards_authority_keypair = Keypair::new();
+        let epoch_rewards = 12345678;
+        let locked_rewards_recipient_address = Pubkey::new_unique();
+        let other_rewards_recipient_address = Pubkey::new_unique();
+
+        // Enter the rewards vault
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::enter(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Rewards authority cannot lock the rewards recipient
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_rewards_recipient(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+                Some(other_rewards_recipient_address),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+
+        // Withdraw authority locks the rewards recipient
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_rewards_recipient(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                Some(locked_rewards_recipient_address),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Simulate epoch rewards
+        let mut transaction = Transaction::new_with_payer(
+            &[system_instruction::transfer(
+                &payer.pubkey(),
+                &vote_account_keypair.pubkey(),
+                epoch_rewards,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], get_new_blockhash(&mut banks_client).await);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Rewards cannot be withdrawn to another recipient
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                other_rewards_recipient_address,
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(code))
+                if code == RewardsVaultError::RewardsRecipientLocked as u32
+        );
+
+        // Rewards can be withdrawn to the locked recipient
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                locked_rewards_recipient_address,
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+        assert_eq!(
+            banks_client
+                .get_balance(locked_rewards_recipient_address)
+                .await
+                .unwrap(),
+            epoch_rewards
+        );
+
+        // Withdraw authority unlocks the rewards recipient
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_rewards_recipient(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                None,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Simulate epoch rewards
+        let mut transaction = Transaction::new_with_payer(
+            &[system_instruction::transfer(
+                &payer.pubkey(),
+                &vote_account_keypair.pubkey(),
+                epoch_rewards,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], get_new_blockhash(&mut banks_client).await);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Rewards can once again be withdrawn to any recipient
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                other_rewards_recipient_address,
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+        assert_eq!(
+            banks_client
+                .get_balance(other_rewards_recipient_address)
+                .await
+                .unwrap(),
+            epoch_rewards
+        );
+    }
+
+    #[tokio::test]
+    async fn test_crank_rewards() {
+        let (mut banks_client, payer, _recent_blockhash) = ProgramTest::new(
+            "sol_rewards_vault_program",
+            crate::id(),
+            processor!(process_instruction),
+        )
+        .start()
+        .await;
+
+        let (vote_account_keypair, authorized_withdrawer_keypair) =
+            create_vote_account(&mut banks_client, &payer).await;
+
+        let minimum_vote_account_balance = banks_client
+            .get_balance(vote_account_keypair.pubkey())
+            .await
+            .unwrap();
+        let rewards_authority_keypair = Keypair::new();
+        let epoch_rewards = 12345678;
+        let locked_rewards_recipient_address = Pubkey::new_unique();
+        let other_rewards_recipient_address = Pubkey::new_unique();
+
+        // Enter the rewards vault
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::enter(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Simulate epoch rewards
+        let mut transaction = Transaction::new_with_payer(
+            &[system_instruction::transfer(
+                &payer.pubkey(),
+                &vote_account_keypair.pubkey(),
+                epoch_rewards,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], get_new_blockhash(&mut banks_client).await);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Crank is disabled by default
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::crank_rewards(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                locked_rewards_recipient_address,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], get_new_blockhash(&mut banks_client).await);
+        assert_matches!(
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(code))
+                if code == RewardsVaultError::CrankNotEnabled as u32
+        );
+
+        // Rewards authority cannot enable the crank
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_crank_mode(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+                true,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+
+        // Withdraw authority enables the crank
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_crank_mode(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                true,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Crank is refused while the rewards recipient is unlocked
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::crank_rewards(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                locked_rewards_recipient_address,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], get_new_blockhash(&mut banks_client).await);
+        assert_matches!(
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(code))
+                if code == RewardsVaultError::RewardsRecipientNotLocked as u32
+        );
+
+        // Lock the rewards recipient
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_rewards_recipient(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                Some(locked_rewards_recipient_address),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Crank cannot sweep to another recipient
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::crank_rewards(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                other_rewards_recipient_address,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], get_new_blockhash(&mut banks_client).await);
+        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+
+        // Anybody may crank the rewards to the locked recipient
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::crank_rewards(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                locked_rewards_recipient_address,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], get_new_blockhash(&mut banks_client).await);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        assert_eq!(
+            banks_client
+                .get_balance(locked_rewards_recipient_address)
+                .await
+                .unwrap(),
+            epoch_rewards
+        );
+        assert_eq!(
+            banks_client
+                .get_balance(vote_account_keypair.pubkey())
+                .await
+                .unwrap(),
+            minimum_vote_account_balance
+        );
+
+        // Withdraw authority disables the crank
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_crank_mode(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                false,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::crank_rewards(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                locked_rewards_recipient_address,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], get_new_blockhash(&mut banks_client).await);
+        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+    }
+
+    #[tokio::test]
+    async fn test_withdraw_limit() {
+        let mut context = ProgramTest::new(
+            "sol_rewards_vault_program",
+            crate::id(),
+            processor!(process_instruction),
+        )
+        .start_with_context()
+        .await;
+        let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+
+        let (vote_account_keypair, authorized_withdrawer_keypair) =
+            create_vote_account(&mut context.banks_client, &payer).await;
+
+        let rewards_authority_keypair = Keypair::new();
+        let epoch_rewards = 12345678;
+        let withdraw_limit = 5000000;
+        let rewards_recipient_address = Pubkey::new_unique();
+
+        // Enter the rewards vault
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::enter(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+
+        // Rewards authority cannot set the withdraw limit
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_withdraw_limit(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+                0,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Err(_)
+        );
+
+        // Withdraw authority sets the withdraw limit
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_withdraw_limit(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                withdraw_limit,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+
+        // Simulate epoch rewards
+        let mut transaction = Transaction::new_with_payer(
+            &[system_instruction::transfer(
+                &payer.pubkey(),
+                &vote_account_keypair.pubkey(),
+                epoch_rewards,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+
+        // Cannot withdraw an amount above the withdraw limit
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards_with_amount(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_recipient_address,
+                rewards_authority_keypair.pubkey(),
+                withdraw_limit + 1,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Err(_)
+        );
+
+        // Withdrawing all rewards is capped at the withdraw limit
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_recipient_address,
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .get_balance(rewards_recipient_address)
+                .await
+                .unwrap(),
+            withdraw_limit
+        );
+
+        // Withdraw limit for this epoch has been reached
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_recipient_address,
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(code))
+                if code == RewardsVaultError::WithdrawLimitExceeded as u32
+        );
+
+        // Advance to the next epoch
+        let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+        let next_epoch_slot = context
+            .genesis_config()
+            .epoch_schedule
+            .get_first_slot_in_epoch(clock.epoch + 1);
+        context.warp_to_slot(next_epoch_slot).unwrap();
+
+        // Withdraw limit applies afresh
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards_with_amount(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_recipient_address,
+                rewards_authority_keypair.pubkey(),
+                withdraw_limit,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .get_balance(rewards_recipient_address)
+                .await
+                .unwrap(),
+            2 * withdraw_limit
+        );
+
+        let vault_account = context
+            .banks_client
+            .get_account(crate::get_rewards_vault_address(
+                &crate::id(),
+                &vote_account_keypair.pubkey(),
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+        let vault_state = bytemuck::from_bytes::<RewardsVaultState>(&vault_account.data);
+        assert_eq!(vault_state.last_withdraw_epoch, clock.epoch + 1);
+        assert_eq!(vault_state.withdrawn_this_epoch, withdraw_limit);
+
+        // Withdraw authority removes the withdraw limit
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_withdraw_limit(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                0,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_recipient_address,
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .get_balance(rewards_recipient_address)
+                .await
+                .unwrap(),
+            epoch_rewards
+        );
+    }
+
+    #[tokio::test]
+    async fn test_withdraw_split() {
+        let (mut banks_client, payer, _recent_blockhash) = ProgramTest::new(
+            "sol_rewards_vault_program",
+            crate::id(),
+            processor!(process_instruction),
+        )
+        .start()
+        .await;
+
+        let (vote_account_keypair, authorized_withdrawer_keypair) =
+            create_vote_account(&mut banks_client, &payer).await;
+
+        let rewards_authority_keypair = Keypair::new();
+        let epoch_rewards = 12345678;
+        let primary_recipient_address = Pubkey::new_unique();
+        let grant_recipient_address = Pubkey::new_unique();
+        let pool_recipient_address = Pubkey::new_unique();
+        let split = [
+            (primary_recipient_address, 5000),
+            (grant_recipient_address, 3333),
+            (pool_recipient_address, 1667),
+        ];
+        let split_recipients = split
+            .iter()
+            .map(|(split_recipient, _)| *split_recipient)
+            .collect::<Vec<_>>();
+
+        // Enter the rewards vault
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::enter(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Cannot withdraw without a split configured
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards_split(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+                &split_recipients,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(code))
+                if code == RewardsVaultError::NoSplitConfigured as u32
+        );
+
+        // Rewards authority cannot set the split
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_split(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+                &split,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+
+        // Split basis points must total 10000
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_split(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                &[
+                    (primary_recipient_address, 5000),
+                    (grant_recipient_address, 4999),
+                ],
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(code))
+                if code == RewardsVaultError::InvalidSplit as u32
+        );
+
+        // Withdraw authority sets the split
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_split(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                &split,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        let vault_account = banks_client
+            .get_account(crate::get_rewards_vault_address(
+                &crate::id(),
+                &vote_account_keypair.pubkey(),
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+        let vault_state = bytemuck::from_bytes::<RewardsVaultState>(&vault_account.data);
+        assert_eq!(vault_state.split(), split);
+
+        // Simulate epoch rewards
+        let mut transaction = Transaction::new_with_payer(
+            &[system_instruction::transfer(
+                &payer.pubkey(),
+                &vote_account_keypair.pubkey(),
+                epoch_rewards,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], get_new_blockhash(&mut banks_client).await);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Split recipients must match the configured split
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards_split(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+                &[
+                    primary_recipient_address,
+                    pool_recipient_address,
+                    grant_recipient_address,
+                ],
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(code))
+                if code == RewardsVaultError::SplitRecipientMismatch as u32
+        );
+
+        // Withdraw the split rewards, with the rounding dust credited to the primary recipient
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards_split(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+                &split_recipients,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+        assert_eq!(
+            banks_client
+                .get_balance(primary_recipient_address)
+                .await
+                .unwrap(),
+            6172840
+        );
+        assert_eq!(
+            banks_client
+                .get_balance(grant_recipient_address)
+                .await
+                .unwrap(),
+            4114814
+        );
+        assert_eq!(
+            banks_client
+                .get_balance(pool_recipient_address)
+                .await
+                .unwrap(),
+            2058024
+        );
+
+        // Withdraw authority clears the split
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_split(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                &[],
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        let vault_account = banks_client
+            .get_account(crate::get_rewards_vault_address(
+                &crate::id(),
+                &vote_account_keypair.pubkey(),
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+        let vault_state = bytemuck::from_bytes::<RewardsVaultState>(&vault_account.data);
+        assert!(vault_state.split().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_withdraw_and_stake() {
+        let (mut banks_client, payer, _recent_blockhash) = ProgramTest::new(
+            "sol_rewards_vault_program",
+            crate::id(),
+            processor!(process_instruction),
+        )
+        .start()
+        .await;
+
+        let (vote_account_keypair, authorized_withdrawer_keypair) =
+            create_vote_account(&mut banks_client, &payer).await;
+
+        let rewards_authority_keypair = Keypair::new();
+        let stake_account_keypair = Keypair::new();
+        let stake_withdraw_authority_address = Pubkey::new_unique();
+        let epoch_rewards = 5_000_000_000;
+
+        // Enter the rewards vault
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::enter(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Simulate epoch rewards
+        let mut transaction = Transaction::new_with_payer(
+            &[system_instruction::transfer(
+                &payer.pubkey(),
+                &vote_account_keypair.pubkey(),
+                epoch_rewards,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], get_new_blockhash(&mut banks_client).await);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Stake withdraw authority must be the locked rewards recipient
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_rewards_recipient(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                Some(Pubkey::new_unique()),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_and_stake(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                stake_account_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+                stake_withdraw_authority_address,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &stake_account_keypair, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Err(_));
+
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_rewards_recipient(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                None,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Rewards that cannot fund a delegated stake account are rejected up front
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_and_stake_with_amount(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                stake_account_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+                stake_withdraw_authority_address,
+                1_000_000,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &stake_account_keypair, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(code))
+                if code == RewardsVaultError::InsufficientStakeAmount as u32
+        );
+
+        // Withdraw the epoch rewards into a new stake account
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_and_stake(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                stake_account_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+                stake_withdraw_authority_address,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &stake_account_keypair, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        let stake_account = banks_client
+            .get_account(stake_account_keypair.pubkey())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stake_account.owner, stake::program::id());
+        assert_eq!(stake_account.lamports, epoch_rewards);
+
+        let stake_state = bincode::deserialize::<StakeState>(&stake_account.data).unwrap();
+        let (meta, stake) = match stake_state {
+            StakeState::Stake(meta, stake) => (meta, stake),
+            _ => panic!("stake account is not delegated: {:?}", stake_state),
+        };
+        assert_eq!(meta.authorized.staker, rewards_authority_keypair.pubkey());
+        assert_eq!(meta.authorized.withdrawer, stake_withdraw_authority_address);
+        assert_eq!(stake.delegation.voter_pubkey, vote_account_keypair.pubkey());
+        assert_eq!(
+            stake.delegation.stake,
+            epoch_rewards - meta.rent_exempt_reserve
+        );
+
+        // Rewards cannot be added to an existing stake account
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                system_instruction::transfer(
+                    &payer.pubkey(),
+                    &vote_account_keypair.pubkey(),
+                    epoch_rewards,
+                ),
+                crate::instruction::withdraw_and_stake(
+                    crate::id(),
+                    vote_account_keypair.pubkey(),
+                    stake_account_keypair.pubkey(),
+                    rewards_authority_keypair.pubkey(),
+                    stake_withdraw_authority_address,
+                ),
+            ],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &stake_account_keypair, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(1, InstructionError::Custom(code))
+                if code == RewardsVaultError::StakeAccountInUse as u32
+        );
+
+        // Nothing to withdraw leaves the new stake account uncreated
+        let new_stake_account_keypair = Keypair::new();
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_and_stake(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                new_stake_account_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+                stake_withdraw_authority_address,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[
+                &payer,
+                &new_stake_account_keypair,
+                &rewards_authority_keypair,
+            ],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+        assert_eq!(
+            banks_client
+                .get_account(new_stake_account_keypair.pubkey())
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_nothing_to_withdraw() {
+        let mut context = ProgramTest::new(
+            "sol_rewards_vault_program",
+            crate::id(),
+            processor!(process_instruction),
+        )
+        .start_with_context()
+        .await;
+        let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+
+        let (vote_account_keypair, authorized_withdrawer_keypair) =
+            create_vote_account(&mut context.banks_client, &payer).await;
+
+        let minimum_vote_account_balance = context
+            .banks_client
+            .get_balance(vote_account_keypair.pubkey())
+            .await
+            .unwrap();
+        let rewards_authority_keypair = Keypair::new();
+        let rewards_recipient_address = Pubkey::new_unique();
+        let epoch_rewards = 12345678;
+
+        // Enter the rewards vault
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::enter(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+
+        let withdraw_rewards = crate::instruction::withdraw_rewards(
+            crate::id(),
+            vote_account_keypair.pubkey(),
+            rewards_recipient_address,
+            rewards_authority_keypair.pubkey(),
+        );
+        let assert_nothing_withdrawn = |log_messages: Vec<String>| {
+            assert!(log_messages.contains(&"Program log: Nothing to withdraw".to_string()));
+            assert!(!log_messages
+                .iter()
+                .any(|log_message| log_message.contains(&vote::program::id().to_string())));
+        };
+
+        // Withdrawing with the vote account at its rent-exempt minimum succeeds without a CPI
+        let mut transaction = Transaction::new_with_payer(
+            std::slice::from_ref(&withdraw_rewards),
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        let result = context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+            .unwrap();
+        assert_matches!(result.result, Ok(()));
+        assert_nothing_withdrawn(result.metadata.unwrap().log_messages);
+        assert_eq!(
+            context
+                .banks_client
+                .get_balance(rewards_recipient_address)
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .get_balance(vote_account_keypair.pubkey())
+                .await
+                .unwrap(),
+            minimum_vote_account_balance
+        );
+
+        // Epoch rewards within the reserve leave nothing to withdraw
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                system_instruction::transfer(
+                    &payer.pubkey(),
+                    &vote_account_keypair.pubkey(),
+                    epoch_rewards,
+                ),
+                crate::instruction::set_reserve(
+                    crate::id(),
+                    vote_account_keypair.pubkey(),
+                    authorized_withdrawer_keypair.pubkey(),
+                    2 * epoch_rewards,
+                ),
+                withdraw_rewards.clone(),
+            ],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[
+                &payer,
+                &authorized_withdrawer_keypair,
+                &rewards_authority_keypair,
+            ],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        let result = context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+            .unwrap();
+        assert_matches!(result.result, Ok(()));
+        assert_nothing_withdrawn(result.metadata.unwrap().log_messages);
+        assert_eq!(
+            context
+                .banks_client
+                .get_balance(rewards_recipient_address)
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .get_balance(vote_account_keypair.pubkey())
+                .await
+                .unwrap(),
+            minimum_vote_account_balance + epoch_rewards
+        );
+
+        // Withdrawing from a vote account below its rent-exempt minimum fails
+        let mut vote_account = context
+            .banks_client
+            .get_account(vote_account_keypair.pubkey())
+            .await
+            .unwrap()
+            .unwrap();
+        vote_account.lamports = minimum_vote_account_balance - 1;
+        context.set_account(
+            &vote_account_keypair.pubkey(),
+            &AccountSharedData::from(vote_account),
+        );
+
+        let mut transaction =
+            Transaction::new_with_payer(&[withdraw_rewards], Some(&payer.pubkey()));
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context
+                .banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(code))
+                if code == RewardsVaultError::VoteAccountBelowMinimumBalance as u32
+        );
+    }
+
+    #[tokio::test]
+    async fn test_migrate() {
+        let mut context = ProgramTest::new(
+            "sol_rewards_vault_program",
+            crate::id(),
+            processor!(process_instruction),
+        )
+        .start_with_context()
+        .await;
+        let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+
+        let (vote_account_keypair, authorized_withdrawer_keypair) =
+            create_vote_account(&mut context.banks_client, &payer).await;
+
+        let rewards_authority_keypair = Keypair::new();
+        let epoch_rewards = 12345678;
+        let rewards_recipient_address = Pubkey::new_unique();
+        let vault_address =
+            crate::get_rewards_vault_address(&crate::id(), &vote_account_keypair.pubkey());
+
+        // Place the vote account in a legacy vault
+        let mut transaction = Transaction::new_with_payer(
+            &[vote::instruction::authorize(
+                &vote_account_keypair.pubkey(),
+                &authorized_withdrawer_keypair.pubkey(),
+                &vault_address,
+                VoteAuthorize::Withdrawer,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+
+        let rent = context.banks_client.get_rent().await.unwrap();
+        context.set_account(
+            &vault_address,
+            &AccountSharedData::from(Account {
+                lamports: rent.minimum_balance(RewardsVaultStateV0::size_of()),
+                data: bytemuck::bytes_of(&RewardsVaultStateV0 {
+                    original_withdraw_authority: authorized_withdrawer_keypair.pubkey(),
+                    rewards_authority: rewards_authority_keypair.pubkey(),
+                })
+                .to_vec(),
+                owner: crate::id(),
+                ..Account::default()
+            }),
+        );
+
+        // Simulate epoch rewards
+        let mut transaction = Transaction::new_with_payer(
+            &[system_instruction::transfer(
+                &payer.pubkey(),
+                &vote_account_keypair.pubkey(),
+                epoch_rewards,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+
+        // Rewards can be withdrawn from a legacy vault
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_recipient_address,
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .get_balance(rewards_recipient_address)
+                .await
+                .unwrap(),
+            epoch_rewards
+        );
+
+        // Clients predating the withdraw limit provide a legacy vault read-only
+        let mut withdraw_rewards_readonly_vault = crate::instruction::withdraw_rewards(
+            crate::id(),
+            vote_account_keypair.pubkey(),
+            rewards_recipient_address,
+            rewards_authority_keypair.pubkey(),
+        );
+        withdraw_rewards_readonly_vault.accounts[0].is_writable = false;
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                system_instruction::transfer(
+                    &payer.pubkey(),
+                    &vote_account_keypair.pubkey(),
+                    epoch_rewards,
+                ),
+                withdraw_rewards_readonly_vault.clone(),
+            ],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .get_balance(rewards_recipient_address)
+                .await
+                .unwrap(),
+            2 * epoch_rewards
+        );
+
+        // A legacy vault cannot hold a reserve
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_reserve(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                1,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context
+                .banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(code))
+                if code == RewardsVaultError::MigrationRequired as u32
+        );
+
+        // Migrate the vault
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::migrate(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+
+        let vault_account = context
+            .banks_client
+            .get_account(vault_address)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(vault_account.data.len(), RewardsVaultState::size_of());
+        assert_eq!(
+            vault_account.lamports,
+            rent.minimum_balance(RewardsVaultState::size_of())
+        );
+        let vault_state = RewardsVaultState::unpack(&vault_account.data).unwrap();
+        assert_eq!(vault_state.version, RewardsVaultState::VERSION);
+        assert_eq!(
+            vault_state.bump_seed,
+            crate::get_rewards_vault_address_and_bump_seed(
+                &crate::id(),
+                &vote_account_keypair.pubkey()
+            )
+            .1
+        );
+        assert_eq!(
+            vault_state.original_withdraw_authority,
+            authorized_withdrawer_keypair.pubkey()
+        );
+        assert_eq!(
+            vault_state.rewards_authority,
+            rewards_authority_keypair.pubkey()
+        );
+
+        // Lifetime accounting starts at migration
+        assert_eq!(vault_state.total_rewards_withdrawn, 0);
+        assert_eq!(vault_state.withdraw_count, 0);
+        assert_eq!(vault_state.entered_epoch, 0);
+        assert_eq!(vault_state.entered_slot, 0);
+
+        // A migrated vault records withdrawals, so it must be provided writable
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                system_instruction::transfer(
+                    &payer.pubkey(),
+                    &vote_account_keypair.pubkey(),
+                    epoch_rewards,
+                ),
+                withdraw_rewards_readonly_vault,
+            ],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context
+                .banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(1, InstructionError::Custom(code))
+                if code == RewardsVaultError::AccountNotWritable as u32
+        );
+
+        // Migrating again is a no-op
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::migrate(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+
+        // The migrated vault can hold a reserve
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_reserve(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                1,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_migrate_v1() {
+        let mut context = ProgramTest::new(
+            "sol_rewards_vault_program",
+            crate::id(),
+            processor!(process_instruction),
+        )
+        .start_with_context()
+        .await;
+        let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+
+        let (vote_account_keypair, authorized_withdrawer_keypair) =
+            create_vote_account(&mut context.banks_client, &payer).await;
+
+        let rewards_authority_keypair = Keypair::new();
+        let epoch_rewards = 12345678;
+        let rewards_recipient_address = Pubkey::new_unique();
+        let (vault_address, bump_seed) = crate::get_rewards_vault_address_and_bump_seed(
+            &crate::id(),
+            &vote_account_keypair.pubkey(),
+        );
+
+        // Place the vote account in a version 1 vault
+        let mut transaction = Transaction::new_with_payer(
+            &[vote::instruction::authorize(
+                &vote_account_keypair.pubkey(),
+                &authorized_withdrawer_keypair.pubkey(),
+                &vault_address,
+                VoteAuthorize::Withdrawer,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+
+        // Fields may only be appended, version 1 vaults keep their size
+        let v1_size = RewardsVaultState::size_of_version(1).unwrap();
+        assert_eq!(v1_size, 312);
+        let rent = context.banks_client.get_rent().await.unwrap();
+        context.set_account(
+            &vault_address,
+            &AccountSharedData::from(Account {
+                lamports: rent.minimum_balance(v1_size),
+                data: bytemuck::bytes_of(&RewardsVaultState {
+                    account_type: 1,
+                    version: 1,
+                    bump_seed,
+                    original_withdraw_authority: authorized_withdrawer_keypair.pubkey(),
+                    rewards_authority: rewards_authority_keypair.pubkey(),
+                    reserve_lamports: 1,
+                    ..RewardsVaultState::zeroed()
+                })[..v1_size]
+                    .to_vec(),
+                owner: crate::id(),
+                ..Account::default()
+            }),
+        );
+
+        // Simulate epoch rewards
+        let mut transaction = Transaction::new_with_payer(
+            &[system_instruction::transfer(
+                &payer.pubkey(),
+                &vote_account_keypair.pubkey(),
+                epoch_rewards,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+
+        // Rewards can be withdrawn from a version 1 vault, which cannot record the accounting
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::withdraw_rewards(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                rewards_recipient_address,
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .get_balance(rewards_recipient_address)
+                .await
+                .unwrap(),
+            epoch_rewards - 1
+        );
+        let vault_account = context
+            .banks_client
+            .get_account(vault_address)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(vault_account.data.len(), v1_size);
+        let vault_state = RewardsVaultState::unpack(&vault_account.data).unwrap();
+        assert_eq!(vault_state.version, 1);
+        assert_eq!(vault_state.total_rewards_withdrawn, 0);
+
+        // Migrate the vault
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::migrate(
+                crate::id(),
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer],
+            get_new_blockhash(&mut context.banks_client).await,
+        );
+        assert_matches!(
+            context.banks_client.process_transaction(transaction).await,
+            Ok(())
+        );
+
+        let vault_account = context
+            .banks_client
+            .get_account(vault_address)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(vault_account.data.len(), RewardsVaultState::size_of());
+        assert_eq!(
+            vault_account.lamports,
+            rent.minimum_balance(RewardsVaultState::size_of())
+        );
+        let vault_state = RewardsVaultState::unpack(&vault_account.data).unwrap();
+        assert_eq!(vault_state.version, RewardsVaultState::VERSION);
+        assert_eq!(vault_state.bump_seed, bump_seed);
+        assert_eq!(vault_state.reserve_lamports, 1);
+        assert_eq!(
+            vault_state.rewards_authority,
+            rewards_authority_keypair.pubkey()
+        );
+        assert_eq!(vault_state.total_rewards_withdrawn, 0);
+    }
+
+    async fn assert_instruction_error(
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        instruction: Instruction,
+        signers: &[&Keypair],
+        error: RewardsVaultError,
+    ) {
+        let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        let mut all_signers = vec![payer];
+        all_signers.extend_from_slice(signers);
+        transaction.sign(&all_signers, get_new_blockhash(banks_client).await);
+        assert_matches!(
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(code))
+                if code == error as u32
+        );
+    }
+
+    #[tokio::test]
+    async fn test_account_validation() {
+        let (mut banks_client, payer, _recent_blockhash) = ProgramTest::new(
+            "sol_rewards_vault_program",
+            crate::id(),
+            processor!(process_instruction),
+        )
+        .start()
+        .await;
+
+        let (vote_account_keypair, authorized_withdrawer_keypair) =
+            create_vote_account(&mut banks_client, &payer).await;
+        let vote_account_address = vote_account_keypair.pubkey();
+        let withdraw_authority_address = authorized_withdrawer_keypair.pubkey();
+
+        let rewards_authority_keypair = Keypair::new();
+        let rewards_authority_address = rewards_authority_keypair.pubkey();
+        let rewards_recipient_address = Pubkey::new_unique();
+        let stake_account_keypair = Keypair::new();
+
+        let wrong_account = || AccountMeta::new_readonly(Pubkey::new_unique(), false);
+
+        // Vault not yet owned by the program
+        assert_instruction_error(
+            &mut banks_client,
+            &payer,
+            crate::instruction::set_reserve(
+                crate::id(),
+                vote_account_address,
+                withdraw_authority_address,
+                1,
+            ),
+            &[&authorized_withdrawer_keypair],
+            RewardsVaultError::IncorrectVaultOwner,
+        )
+        .await;
+
+        // Not a vote account
+        assert_instruction_error(
+            &mut banks_client,
+            &payer,
+            crate::instruction::enter(
+                crate::id(),
+                Pubkey::new_unique(),
+                payer.pubkey(),
+                withdraw_authority_address,
+                rewards_authority_address,
+            ),
+            &[&authorized_withdrawer_keypair],
+            RewardsVaultError::IncorrectVoteAccountOwner,
+        )
+        .await;
+
+        let enter = crate::instruction::enter(
+            crate::id(),
+            vote_account_address,
+            payer.pubkey(),
+            withdraw_authority_address,
+            rewards_authority_address,
+        );
+        for (index, account, error) in [
+            (
+                0,
+                AccountMeta::new_readonly(enter.accounts[0].pubkey, false),
+                RewardsVaultError::AccountNotWritable,
+            ),
+            (
+                1,
+                AccountMeta::new_readonly(vote_account_address, false),
+                RewardsVaultError::AccountNotWritable,
+            ),
+            (
+                5,
+                wrong_account(),
+                RewardsVaultError::IncorrectSystemProgram,
+            ),
+            (6, wrong_account(), RewardsVaultError::IncorrectVoteProgram),
+            (
+                7,
+                AccountMeta::new_readonly(sysvar::rent::id(), false),
+                RewardsVaultError::IncorrectSysvar,
+            ),
+        ] {
+            let mut instruction = enter.clone();
+            instruction.accounts[index] = account;
+            assert_instruction_error(
+                &mut banks_client,
+                &payer,
+                instruction,
+                &[&authorized_withdrawer_keypair],
+                error,
+            )
+            .await;
+        }
+
+        // Authorities must sign
+        let mut instruction = enter.clone();
+        instruction.accounts[2] = AccountMeta::new(Pubkey::new_unique(), false);
+        assert_instruction_error(
+            &mut banks_client,
+            &payer,
+            instruction,
+            &[&authorized_withdrawer_keypair],
+            RewardsVaultError::AccountNotSigner,
+        )
+        .await;
+
+        let mut instruction = enter.clone();
+        instruction.accounts[3].is_signer = false;
+        assert_instruction_error(
+            &mut banks_client,
+            &payer,
+            instruction,
+            &[],
+            RewardsVaultError::AccountNotSigner,
+        )
+        .await;
+
+        // Enter the rewards vault
+        let mut transaction = Transaction::new_with_payer(&[enter], Some(&payer.pubkey()));
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        // Simulate epoch rewards
+        let mut transaction = Transaction::new_with_payer(
+            &[system_instruction::transfer(
+                &payer.pubkey(),
+                &vote_account_address,
+                5_000_000_000,
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], get_new_blockhash(&mut banks_client).await);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        let withdraw_rewards = crate::instruction::withdraw_rewards(
+            crate::id(),
+            vote_account_address,
+            rewards_recipient_address,
+            rewards_authority_address,
+        );
+        for (index, account, error) in [
+            (
+                2,
+                AccountMeta::new_readonly(rewards_recipient_address, false),
+                RewardsVaultError::AccountNotWritable,
+            ),
+            (4, wrong_account(), RewardsVaultError::IncorrectVoteProgram),
+        ] {
+            let mut instruction = withdraw_rewards.clone();
+            instruction.accounts[index] = account;
+            assert_instruction_error(
+                &mut banks_client,
+                &payer,
+                instruction,
+                &[&rewards_authority_keypair],
+                error,
+            )
+            .await;
+        }
+
+        let mut instruction = withdraw_rewards.clone();
+        instruction.accounts[3].is_signer = false;
+        assert_instruction_error(
+            &mut banks_client,
+            &payer,
+            instruction,
+            &[],
+            RewardsVaultError::AccountNotSigner,
+        )
+        .await;
+
+        let withdraw_and_stake = crate::instruction::withdraw_and_stake(
+            crate::id(),
+            vote_account_address,
+            stake_account_keypair.pubkey(),
+            rewards_authority_address,
+            rewards_recipient_address,
+        );
+        for (index, account, error) in [
+            (
+                2,
+                AccountMeta::new_readonly(stake_account_keypair.pubkey(), true),
+                RewardsVaultError::AccountNotWritable,
+            ),
+            (5, wrong_account(), RewardsVaultError::IncorrectVoteProgram),
+            (
+                6,
+                wrong_account(),
+                RewardsVaultError::IncorrectSystemProgram,
+            ),
+            (7, wrong_account(), RewardsVaultError::IncorrectStakeProgram),
+            (8, wrong_account(), RewardsVaultError::IncorrectSysvar),
+            (9, wrong_account(), RewardsVaultError::IncorrectSysvar),
+            (10, wrong_account(), RewardsVaultError::IncorrectSysvar),
+            (11, wrong_account(), RewardsVaultError::IncorrectStakeConfig),
+        ] {
+            let mut instruction = withdraw_and_stake.clone();
+            instruction.accounts[index] = account;
+            assert_instruction_error(
+                &mut banks_client,
+                &payer,
+                instruction,
+                &[&stake_account_keypair, &rewards_authority_keypair],
+                error,
+            )
+            .await;
+        }
+
+        let mut instruction = withdraw_and_stake.clone();
+        instruction.accounts[2].is_signer = false;
+        assert_instruction_error(
+            &mut banks_client,
+            &payer,
+            instruction,
+            &[&rewards_authority_keypair],
+            RewardsVaultError::AccountNotSigner,
+        )
+        .await;
+
+        let mut instruction = withdraw_and_stake.clone();
+        instruction.accounts[3].is_signer = false;
+        assert_instruction_error(
+            &mut banks_client,
+            &payer,
+            instruction,
+            &[&stake_account_keypair],
+            RewardsVaultError::AccountNotSigner,
+        )
+        .await;
+
+        // Split recipients must be writable
+        let split_recipient_address = Pubkey::new_unique();
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::set_split(
+                crate::id(),
+                vote_account_address,
+                withdraw_authority_address,
+                &[(split_recipient_address, SPLIT_TOTAL_BPS)],
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        let mut instruction = crate::instruction::withdraw_rewards_split(
+            crate::id(),
+            vote_account_address,
+            rewards_authority_address,
+            &[split_recipient_address],
+        );
+        instruction.accounts[4] = AccountMeta::new_readonly(split_recipient_address, false);
+        assert_instruction_error(
+            &mut banks_client,
+            &payer,
+            instruction,
+            &[&rewards_authority_keypair],
+            RewardsVaultError::AccountNotWritable,
+        )
+        .await;
+
+        // The vault must be writable to update its state
+        let mut instruction = crate::instruction::set_reserve(
+            crate::id(),
+            vote_account_address,
+            withdraw_authority_address,
+            1,
+        );
+        instruction.accounts[0].is_writable = false;
+        assert_instruction_error(
+            &mut banks_client,
+            &payer,
+            instruction,
+            &[&authorized_withdrawer_keypair],
+            RewardsVaultError::AccountNotWritable,
+        )
+        .await;
+
+        // The vote account must be writable for vote program instructions
+        let mut instruction = crate::instruction::update_commission(
+            crate::id(),
+            vote_account_address,
+            withdraw_authority_address,
+            10,
+        );
+        instruction.accounts[1].is_writable = false;
+        assert_instruction_error(
+            &mut banks_client,
+            &payer,
+            instruction,
+            &[&authorized_withdrawer_keypair],
+            RewardsVaultError::AccountNotWritable,
+        )
+        .await;
+
+        let authorize_voter = crate::instruction::authorize_voter(
+            crate::id(),
+            vote_account_address,
+            withdraw_authority_address,
+            Pubkey::new_unique(),
+        );
+        let mut instruction = authorize_voter.clone();
+        instruction.accounts[2].is_signer = false;
+        assert_instruction_error(
+            &mut banks_client,
+            &payer,
+            instruction,
+            &[],
+            RewardsVaultError::AccountNotSigner,
+        )
+        .await;
+
+        let mut instruction = authorize_voter.clone();
+        instruction.accounts[5] = wrong_account();
+        assert_instruction_error(
+            &mut banks_client,
+            &payer,
+            instruction,
+            &[&authorized_withdrawer_keypair],
+            RewardsVaultError::IncorrectSysvar,
+        )
+        .await;
+
+        let new_identity_keypair = Keypair::new();
+        let update_validator_identity = crate::instruction::update_validator_identity(
+            crate::id(),
+            vote_account_address,
+            withdraw_authority_address,
+            new_identity_keypair.pubkey(),
+        );
+        let mut instruction = update_validator_identity.clone();
+        instruction.accounts[2].is_signer = false;
+        assert_instruction_error(
+            &mut banks_client,
+            &payer,
+            instruction,
+            &[&new_identity_keypair],
+            RewardsVaultError::AccountNotSigner,
+        )
+        .await;
+
+        let mut instruction = update_validator_identity.clone();
+        instruction.accounts[3].is_signer = false;
+        assert_instruction_error(
+            &mut banks_client,
+            &payer,
+            instruction,
+            &[&authorized_withdrawer_keypair],
+            RewardsVaultError::AccountNotSigner,
+        )
+        .await;
+
+        let migrate =
+            crate::instruction::migrate(crate::id(), vote_account_address, payer.pubkey());
+        let mut instruction = migrate.clone();
+        instruction.accounts[2] = AccountMeta::new(Pubkey::new_unique(), false);
+        assert_instruction_error(
+            &mut banks_client,
+            &payer,
+            instruction,
+            &[],
+            RewardsVaultError::AccountNotSigner,
+        )
+        .await;
+
+        let mut instruction = migrate;
+        instruction.accounts[3] = wrong_account();
+        assert_instruction_error(
+            &mut banks_client,
+            &payer,
+            instruction,
+            &[],
+            RewardsVaultError::IncorrectSystemProgram,
+        )
+        .await;
+
+        let leave = crate::instruction::leave(
+            crate::id(),
+            vote_account_address,
+            payer.pubkey(),
+            withdraw_authority_address,
+        );
+        for (index, account, error) in [
+            (4, wrong_account(), RewardsVaultError::IncorrectVoteProgram),
+            (5, wrong_account(), RewardsVaultError::IncorrectSysvar),
+        ] {
+            let mut instruction = leave.clone();
+            instruction.accounts[index] = account;
+            assert_instruction_error(
+                &mut banks_client,
+                &payer,
+                instruction,
+                &[&authorized_withdrawer_keypair],
+                error,
+            )
+            .await;
+        }
+
+        let mut instruction = leave.clone();
+        instruction.accounts[3].is_signer = false;
+        assert_instruction_error(
+            &mut banks_client,
+            &payer,
+            instruction,
+            &[],
+            RewardsVaultError::AccountNotSigner,
+        )
+        .await;
+
+        // The vault still works with the expected accounts
+        let mut transaction = Transaction::new_with_payer(&[leave], Some(&payer.pubkey()));
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_foreign_program_id() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, _recent_blockhash) = ProgramTest::new(
+            "sol_rewards_vault_program",
+            program_id,
+            processor!(process_instruction),
+        )
+        .start()
+        .await;
+
+        let (vote_account_keypair, authorized_withdrawer_keypair) =
+            create_vote_account(&mut banks_client, &payer).await;
+        let rewards_authority_keypair = Keypair::new();
+        let rewards_recipient_address = Pubkey::new_unique();
+        let vault_address =
+            crate::get_rewards_vault_address(&program_id, &vote_account_keypair.pubkey());
+        assert_ne!(
+            vault_address,
+            crate::get_rewards_vault_address(&crate::id(), &vote_account_keypair.pubkey())
+        );
+
+        // Enter the rewards vault of the program deployed at `program_id`
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::enter(
+                program_id,
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+                rewards_authority_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        let vault_account = banks_client
+            .get_account(vault_address)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(vault_account.owner, program_id);
+
+        // Simulate epoch rewards and withdraw them
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                system_instruction::transfer(
+                    &payer.pubkey(),
+                    &vote_account_keypair.pubkey(),
+                    12345678,
+                ),
+                crate::instruction::withdraw_rewards(
+                    program_id,
+                    vote_account_keypair.pubkey(),
+                    rewards_recipient_address,
+                    rewards_authority_keypair.pubkey(),
+                ),
+            ],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &rewards_authority_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+        assert_eq!(
+            banks_client
+                .get_balance(rewards_recipient_address)
+                .await
+                .unwrap(),
+            12345678
+        );
+
+        // Instructions built for another program id do not reach this deployment
+        let mut instruction = crate::instruction::leave(
+            crate::id(),
+            vote_account_keypair.pubkey(),
+            payer.pubkey(),
+            authorized_withdrawer_keypair.pubkey(),
+        );
+        instruction.program_id = program_id;
+        let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(code))
+                if code == RewardsVaultError::IncorrectVaultOwner as u32
+        );
+
+        // Leave the rewards vault
+        let mut transaction = Transaction::new_with_payer(
+            &[crate::instruction::leave(
+                program_id,
+                vote_account_keypair.pubkey(),
+                payer.pubkey(),
+                authorized_withdrawer_keypair.pubkey(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(
+            &[&payer, &authorized_withdrawer_keypair],
+            get_new_blockhash(&mut banks_client).await,
+        );
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+    }
 }
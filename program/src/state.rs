@@ -1,17 +1,213 @@
 use {
+    crate::error::RewardsVaultError,
     bytemuck::{Pod, Zeroable},
-    solana_program::pubkey::Pubkey,
+    solana_program::{program_error::ProgramError, pubkey::Pubkey},
 };
 
+/// `RewardsVaultState::account_type` of an initialized vault account
+pub const ACCOUNT_TYPE_REWARDS_VAULT: u8 = 1;
+
+/// Maximum number of recipients epoch rewards may be split across
+pub const MAX_SPLIT_RECIPIENTS: usize = 4;
+
+/// Total basis points of a rewards split
+pub const SPLIT_TOTAL_BPS: u16 = 10_000;
+
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable, PartialEq, Eq)]
 pub struct RewardsVaultState {
+    /// `ACCOUNT_TYPE_REWARDS_VAULT` once the vault is initialized
+    pub account_type: u8,
+    /// Layout version of the vault account, `RewardsVaultState::VERSION` unless the vault still
+    /// uses an earlier layout
+    pub version: u8,
+    /// Bump seed of the vault address, sparing later instructions the bump seed search
+    pub bump_seed: u8,
+    pub _header_padding: [u8; 5],
+    pub original_withdraw_authority: Pubkey,
+    pub rewards_authority: Pubkey,
+    /// Nominated replacement for `original_withdraw_authority`, or `Pubkey::default()` if none
+    pub pending_withdraw_authority: Pubkey,
+    /// Lamports retained in the vote account, in addition to its rent-exempt minimum balance, that
+    /// the rewards authority may never withdraw
+    pub reserve_lamports: u64,
+    /// The only account epoch rewards may be withdrawn to, or `Pubkey::default()` if the rewards
+    /// authority may choose the recipient
+    pub locked_rewards_recipient: Pubkey,
+    /// Non-zero if anybody may sweep epoch rewards to `locked_rewards_recipient`
+    pub crank_enabled: u8,
+    pub _padding: [u8; 7],
+    /// Maximum lamports that may be withdrawn per epoch, or 0 if unlimited
+    pub max_withdraw_per_epoch: u64,
+    /// Epoch of the most recent withdrawal
+    pub last_withdraw_epoch: u64,
+    /// Lamports withdrawn during `last_withdraw_epoch`
+    pub withdrawn_this_epoch: u64,
+    /// Recipients epoch rewards are split across. The first recipient is the primary recipient and
+    /// also receives any rounding dust
+    pub split_recipients: [Pubkey; MAX_SPLIT_RECIPIENTS],
+    /// Basis points of epoch rewards credited to the corresponding `split_recipients` entry, or 0
+    /// for unused entries
+    pub split_bps: [u16; MAX_SPLIT_RECIPIENTS],
+    /// Lamports withdrawn from the vote account since it entered the vault or, for a migrated
+    /// vault, since it was migrated to version 2
+    pub total_rewards_withdrawn: u64,
+    /// Number of withdrawals counted in `total_rewards_withdrawn`
+    pub withdraw_count: u64,
+    /// Epoch the vote account entered the vault, or 0 for a migrated vault
+    pub entered_epoch: u64,
+    /// Slot the vote account entered the vault, or 0 for a migrated vault
+    pub entered_slot: u64,
+}
+
+/// Layout of vault accounts created before the account header was introduced, now version 0
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable, PartialEq, Eq)]
+pub struct RewardsVaultStateV0 {
     pub original_withdraw_authority: Pubkey,
     pub rewards_authority: Pubkey,
 }
 
+impl RewardsVaultStateV0 {
+    pub fn size_of() -> usize {
+        std::mem::size_of::<Self>()
+    }
+}
+
+impl From<RewardsVaultStateV0> for RewardsVaultState {
+    fn from(vault_state: RewardsVaultStateV0) -> Self {
+        Self {
+            account_type: ACCOUNT_TYPE_REWARDS_VAULT,
+            original_withdraw_authority: vault_state.original_withdraw_authority,
+            rewards_authority: vault_state.rewards_authority,
+            ..Self::zeroed()
+        }
+    }
+}
+
 impl RewardsVaultState {
+    pub const VERSION: u8 = 2;
+
     pub fn size_of() -> usize {
         std::mem::size_of::<Self>()
     }
+
+    /// Returns the size of a vault account of layout `version`. Version 1 is the current layout
+    /// without the lifetime accounting fields that follow `split_bps`
+    pub fn size_of_version(version: u8) -> Option<usize> {
+        match version {
+            0 => Some(RewardsVaultStateV0::size_of()),
+            1 => Some(bytemuck::offset_of!(
+                Self::zeroed(),
+                Self,
+                total_rewards_withdrawn
+            )),
+            Self::VERSION => Some(Self::size_of()),
+            _ => None,
+        }
+    }
+
+    /// Returns the offset of `original_withdraw_authority` within a vault account of layout
+    /// `version`, for filtering vault accounts by authority
+    pub fn original_withdraw_authority_offset(version: u8) -> usize {
+        if version == 0 {
+            bytemuck::offset_of!(
+                RewardsVaultStateV0::zeroed(),
+                RewardsVaultStateV0,
+                original_withdraw_authority
+            )
+        } else {
+            bytemuck::offset_of!(Self::zeroed(), Self, original_withdraw_authority)
+        }
+    }
+
+    /// Returns the offset of `rewards_authority` within a vault account of layout `version`
+    pub fn rewards_authority_offset(version: u8) -> usize {
+        if version == 0 {
+            bytemuck::offset_of!(
+                RewardsVaultStateV0::zeroed(),
+                RewardsVaultStateV0,
+                rewards_authority
+            )
+        } else {
+            bytemuck::offset_of!(Self::zeroed(), Self, rewards_authority)
+        }
+    }
+
+    fn version_of_size(size: usize) -> Option<u8> {
+        (0..=Self::VERSION).find(|version| Self::size_of_version(*version) == Some(size))
+    }
+
+    /// Deserializes an initialized vault account of the current or an earlier layout. Fields
+    /// missing from an earlier layout are zero and a legacy `RewardsVaultStateV0` vault is
+    /// reported with a `version` of 0
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() == RewardsVaultStateV0::size_of() {
+            return bytemuck::try_from_bytes::<RewardsVaultStateV0>(data)
+                .map(|vault_state| (*vault_state).into())
+                .map_err(|_| ProgramError::InvalidAccountData);
+        }
+
+        let version = Self::version_of_size(data.len()).ok_or(ProgramError::InvalidAccountData)?;
+        let mut vault_state = Self::zeroed();
+        bytemuck::bytes_of_mut(&mut vault_state)[..data.len()].copy_from_slice(data);
+        if vault_state.account_type != ACCOUNT_TYPE_REWARDS_VAULT || vault_state.version != version
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(vault_state)
+    }
+
+    /// Serializes into a vault account, preserving its layout. A legacy vault can only hold the
+    /// fields of `RewardsVaultStateV0`, any other setting requires migrating the vault first.
+    /// Withdraw bookkeeping and lifetime accounting are dropped for layouts that lack them
+    pub fn pack(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        if data.len() == RewardsVaultStateV0::size_of() {
+            let vault_state = RewardsVaultStateV0 {
+                original_withdraw_authority: self.original_withdraw_authority,
+                rewards_authority: self.rewards_authority,
+            };
+
+            // Withdraw bookkeeping is only meaningful with a withdraw limit, which a legacy vault
+            // cannot hold, so it may be dropped
+            if RewardsVaultState::from(vault_state)
+                != (Self {
+                    version: 0,
+                    bump_seed: 0,
+                    last_withdraw_epoch: 0,
+                    withdrawn_this_epoch: 0,
+                    total_rewards_withdrawn: 0,
+                    withdraw_count: 0,
+                    entered_epoch: 0,
+                    entered_slot: 0,
+                    ..*self
+                })
+            {
+                return Err(RewardsVaultError::MigrationRequired.into());
+            }
+
+            data.copy_from_slice(bytemuck::bytes_of(&vault_state));
+            return Ok(());
+        }
+
+        let version = Self::version_of_size(data.len()).ok_or(ProgramError::InvalidAccountData)?;
+        let vault_state = Self {
+            account_type: ACCOUNT_TYPE_REWARDS_VAULT,
+            version,
+            ..*self
+        };
+        data.copy_from_slice(&bytemuck::bytes_of(&vault_state)[..data.len()]);
+        Ok(())
+    }
+
+    /// Returns the configured rewards split as `(recipient, basis points)` pairs, primary recipient
+    /// first. Empty if no split is configured
+    pub fn split(&self) -> Vec<(Pubkey, u16)> {
+        self.split_recipients
+            .iter()
+            .copied()
+            .zip(self.split_bps.iter().copied())
+            .take_while(|(_, bps)| *bps != 0)
+            .collect()
+    }
 }
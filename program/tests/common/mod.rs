@@ -0,0 +1,73 @@
+use {
+    assert_matches::*,
+    solana_program::{
+        hash::Hash,
+        pubkey::Pubkey,
+        vote::{
+            self,
+            state::{VoteInit, VoteState},
+        },
+    },
+    solana_program_test::*,
+    solana_sdk::{
+        signature::{Keypair, Signer},
+        transaction::Transaction,
+    },
+    tokio::time::{sleep, Duration},
+};
+
+pub async fn create_vote_account(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+) -> (
+    /*vote_account_keypair*/ Keypair,
+    /*authorized_withdrawer_keypair*/ Keypair,
+) {
+    let node_pubkey_keypair = Keypair::new();
+    let vote_account_keypair = Keypair::new();
+    let authorized_withdrawer_keypair = Keypair::new();
+
+    let rent = banks_client.get_rent().await.unwrap();
+
+    let mut transaction = Transaction::new_with_payer(
+        &vote::instruction::create_account(
+            &payer.pubkey(),
+            &vote_account_keypair.pubkey(),
+            &VoteInit {
+                node_pubkey: node_pubkey_keypair.pubkey(),
+                authorized_voter: Pubkey::new_unique(),
+                authorized_withdrawer: authorized_withdrawer_keypair.pubkey(),
+                commission: 42,
+            },
+            rent.minimum_balance(VoteState::size_of()),
+        ),
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(
+        &[payer, &vote_account_keypair, &node_pubkey_keypair],
+        banks_client
+            .get_latest_blockhash()
+            .await
+            .expect("blockhash"),
+    );
+    assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+    (vote_account_keypair, authorized_withdrawer_keypair)
+}
+
+pub async fn get_new_blockhash(banks_client: &mut BanksClient) -> Hash {
+    let current_blockhash = banks_client
+        .get_latest_blockhash()
+        .await
+        .expect("blockhash");
+    loop {
+        let new_blockhash = banks_client
+            .get_latest_blockhash()
+            .await
+            .expect("blockhash");
+        if new_blockhash != current_blockhash {
+            return new_blockhash;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+}
@@ -0,0 +1,365 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use {
+    assert_matches::*,
+    common::{create_vote_account, get_new_blockhash},
+    sol_rewards_vault_program::{
+        get_rewards_vault_address, id, instruction, state::RewardsVaultStateV0,
+    },
+    solana_program::{instruction::Instruction, pubkey::Pubkey, system_instruction},
+    solana_program_test::*,
+    solana_sdk::{
+        account::{Account, AccountSharedData},
+        signature::{Keypair, Signer},
+        transaction::Transaction,
+    },
+};
+
+/// Processes `instruction` and asserts that it succeeded within `max_compute_units`
+async fn assert_compute_units(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    instruction: Instruction,
+    signers: &[&Keypair],
+    max_compute_units: u64,
+) {
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    transaction.sign(&all_signers, get_new_blockhash(banks_client).await);
+
+    let result = banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    assert_matches!(result.result, Ok(()));
+
+    let compute_units_consumed = result.metadata.unwrap().compute_units_consumed;
+    assert!(
+        compute_units_consumed <= max_compute_units,
+        "consumed {} compute units, expected at most {}",
+        compute_units_consumed,
+        max_compute_units
+    );
+}
+
+#[tokio::test]
+async fn test_compute_units() {
+    let (mut banks_client, payer, _recent_blockhash) =
+        ProgramTest::new("sol_rewards_vault_program", id(), None)
+            .start()
+            .await;
+
+    let (vote_account_keypair, authorized_withdrawer_keypair) =
+        create_vote_account(&mut banks_client, &payer).await;
+    let vote_account_address = vote_account_keypair.pubkey();
+    let withdraw_authority_address = authorized_withdrawer_keypair.pubkey();
+
+    let rewards_authority_keypair = Keypair::new();
+    let rewards_authority_address = rewards_authority_keypair.pubkey();
+    let rewards_recipient_address = Pubkey::new_unique();
+    let split_recipient_address = Pubkey::new_unique();
+    let stake_account_keypair = Keypair::new();
+    let new_identity_keypair = Keypair::new();
+    let new_withdraw_authority_keypair = Keypair::new();
+
+    assert_compute_units(
+        &mut banks_client,
+        &payer,
+        instruction::enter(
+            id(),
+            vote_account_address,
+            payer.pubkey(),
+            withdraw_authority_address,
+            rewards_authority_address,
+        ),
+        &[&authorized_withdrawer_keypair],
+        40_000,
+    )
+    .await;
+
+    assert_compute_units(
+        &mut banks_client,
+        &payer,
+        instruction::set_rewards_authority(
+            id(),
+            vote_account_address,
+            withdraw_authority_address,
+            rewards_authority_address,
+        ),
+        &[&authorized_withdrawer_keypair],
+        10_000,
+    )
+    .await;
+
+    assert_compute_units(
+        &mut banks_client,
+        &payer,
+        instruction::set_reserve(id(), vote_account_address, withdraw_authority_address, 0),
+        &[&authorized_withdrawer_keypair],
+        10_000,
+    )
+    .await;
+
+    assert_compute_units(
+        &mut banks_client,
+        &payer,
+        instruction::set_withdraw_limit(id(), vote_account_address, withdraw_authority_address, 0),
+        &[&authorized_withdrawer_keypair],
+        10_000,
+    )
+    .await;
+
+    assert_compute_units(
+        &mut banks_client,
+        &payer,
+        instruction::update_commission(id(), vote_account_address, withdraw_authority_address, 10),
+        &[&authorized_withdrawer_keypair],
+        20_000,
+    )
+    .await;
+
+    assert_compute_units(
+        &mut banks_client,
+        &payer,
+        instruction::update_validator_identity(
+            id(),
+            vote_account_address,
+            withdraw_authority_address,
+            new_identity_keypair.pubkey(),
+        ),
+        &[&authorized_withdrawer_keypair, &new_identity_keypair],
+        20_000,
+    )
+    .await;
+
+    assert_compute_units(
+        &mut banks_client,
+        &payer,
+        instruction::authorize_voter(
+            id(),
+            vote_account_address,
+            withdraw_authority_address,
+            Pubkey::new_unique(),
+        ),
+        &[&authorized_withdrawer_keypair],
+        20_000,
+    )
+    .await;
+
+    assert_compute_units(
+        &mut banks_client,
+        &payer,
+        instruction::propose_withdraw_authority(
+            id(),
+            vote_account_address,
+            withdraw_authority_address,
+            Pubkey::new_unique(),
+        ),
+        &[&authorized_withdrawer_keypair],
+        10_000,
+    )
+    .await;
+
+    assert_compute_units(
+        &mut banks_client,
+        &payer,
+        instruction::cancel_withdraw_authority(
+            id(),
+            vote_account_address,
+            withdraw_authority_address,
+        ),
+        &[&authorized_withdrawer_keypair],
+        10_000,
+    )
+    .await;
+
+    // Simulate epoch rewards, enough to fund a delegated stake account
+    let mut transaction = Transaction::new_with_payer(
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            &vote_account_address,
+            10_000_000_000,
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], get_new_blockhash(&mut banks_client).await);
+    assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+    assert_compute_units(
+        &mut banks_client,
+        &payer,
+        instruction::withdraw_rewards_with_amount(
+            id(),
+            vote_account_address,
+            rewards_recipient_address,
+            rewards_authority_address,
+            1_000_000_000,
+        ),
+        &[&rewards_authority_keypair],
+        20_000,
+    )
+    .await;
+
+    assert_compute_units(
+        &mut banks_client,
+        &payer,
+        instruction::withdraw_and_stake_with_amount(
+            id(),
+            vote_account_address,
+            stake_account_keypair.pubkey(),
+            rewards_authority_address,
+            rewards_recipient_address,
+            5_000_000_000,
+        ),
+        &[&stake_account_keypair, &rewards_authority_keypair],
+        60_000,
+    )
+    .await;
+
+    assert_compute_units(
+        &mut banks_client,
+        &payer,
+        instruction::set_split(
+            id(),
+            vote_account_address,
+            withdraw_authority_address,
+            &[
+                (rewards_recipient_address, 5000),
+                (split_recipient_address, 5000),
+            ],
+        ),
+        &[&authorized_withdrawer_keypair],
+        10_000,
+    )
+    .await;
+
+    assert_compute_units(
+        &mut banks_client,
+        &payer,
+        instruction::withdraw_rewards_split(
+            id(),
+            vote_account_address,
+            rewards_authority_address,
+            &[rewards_recipient_address, split_recipient_address],
+        ),
+        &[&rewards_authority_keypair],
+        30_000,
+    )
+    .await;
+
+    assert_compute_units(
+        &mut banks_client,
+        &payer,
+        instruction::set_rewards_recipient(
+            id(),
+            vote_account_address,
+            withdraw_authority_address,
+            Some(rewards_recipient_address),
+        ),
+        &[&authorized_withdrawer_keypair],
+        10_000,
+    )
+    .await;
+
+    assert_compute_units(
+        &mut banks_client,
+        &payer,
+        instruction::set_crank_mode(id(), vote_account_address, withdraw_authority_address, true),
+        &[&authorized_withdrawer_keypair],
+        10_000,
+    )
+    .await;
+
+    assert_compute_units(
+        &mut banks_client,
+        &payer,
+        instruction::crank_rewards(id(), vote_account_address, rewards_recipient_address),
+        &[],
+        20_000,
+    )
+    .await;
+
+    // Hand the vault to a new withdraw authority before leaving
+    let mut transaction = Transaction::new_with_payer(
+        &[instruction::propose_withdraw_authority(
+            id(),
+            vote_account_address,
+            withdraw_authority_address,
+            new_withdraw_authority_keypair.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(
+        &[&payer, &authorized_withdrawer_keypair],
+        get_new_blockhash(&mut banks_client).await,
+    );
+    assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+    assert_compute_units(
+        &mut banks_client,
+        &payer,
+        instruction::accept_withdraw_authority(
+            id(),
+            vote_account_address,
+            new_withdraw_authority_keypair.pubkey(),
+        ),
+        &[&new_withdraw_authority_keypair],
+        10_000,
+    )
+    .await;
+
+    assert_compute_units(
+        &mut banks_client,
+        &payer,
+        instruction::leave(
+            id(),
+            vote_account_address,
+            payer.pubkey(),
+            new_withdraw_authority_keypair.pubkey(),
+        ),
+        &[&new_withdraw_authority_keypair],
+        30_000,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_migrate_compute_units() {
+    let mut context = ProgramTest::new("sol_rewards_vault_program", id(), None)
+        .start_with_context()
+        .await;
+    let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+
+    let (vote_account_keypair, authorized_withdrawer_keypair) =
+        create_vote_account(&mut context.banks_client, &payer).await;
+    let vote_account_address = vote_account_keypair.pubkey();
+
+    // A legacy vault lacks the stored bump seed and must be reallocated, the most expensive
+    // migration
+    let rent = context.banks_client.get_rent().await.unwrap();
+    context.set_account(
+        &get_rewards_vault_address(&id(), &vote_account_address),
+        &AccountSharedData::from(Account {
+            lamports: rent.minimum_balance(RewardsVaultStateV0::size_of()),
+            data: bytemuck::bytes_of(&RewardsVaultStateV0 {
+                original_withdraw_authority: authorized_withdrawer_keypair.pubkey(),
+                rewards_authority: Pubkey::new_unique(),
+            })
+            .to_vec(),
+            owner: id(),
+            ..Account::default()
+        }),
+    );
+
+    assert_compute_units(
+        &mut context.banks_client,
+        &payer,
+        instruction::migrate(id(), vote_account_address, payer.pubkey()),
+        &[],
+        30_000,
+    )
+    .await;
+}
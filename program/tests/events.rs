@@ -0,0 +1,273 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use {
+    assert_matches::*,
+    common::{create_vote_account, get_new_blockhash},
+    sol_rewards_vault_program::{
+        event::{parse_logs, RewardsVaultEvent},
+        get_rewards_vault_address, id, instruction,
+        state::{RewardsVaultState, RewardsVaultStateV0},
+    },
+    solana_program::{instruction::Instruction, pubkey::Pubkey, system_instruction},
+    solana_program_test::*,
+    solana_sdk::{
+        account::{Account, AccountSharedData},
+        signature::{Keypair, Signer},
+        transaction::Transaction,
+    },
+};
+
+/// Processes `instructions` and returns the rewards vault events they logged
+async fn process_events(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Vec<RewardsVaultEvent> {
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    transaction.sign(&all_signers, get_new_blockhash(banks_client).await);
+
+    let result = banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    assert_matches!(result.result, Ok(()));
+
+    parse_logs(&id(), &result.metadata.unwrap().log_messages)
+}
+
+#[tokio::test]
+async fn test_events() {
+    let (mut banks_client, payer, _recent_blockhash) =
+        ProgramTest::new("sol_rewards_vault_program", id(), None)
+            .start()
+            .await;
+
+    let epoch = banks_client
+        .get_sysvar::<solana_program::clock::Clock>()
+        .await
+        .unwrap()
+        .epoch;
+
+    let (vote_account_keypair, authorized_withdrawer_keypair) =
+        create_vote_account(&mut banks_client, &payer).await;
+    let vote_account = vote_account_keypair.pubkey();
+    let withdraw_authority = authorized_withdrawer_keypair.pubkey();
+    let rewards_authority_keypair = Keypair::new();
+    let rewards_authority = rewards_authority_keypair.pubkey();
+    let rewards_recipient = Pubkey::new_unique();
+    let new_rewards_authority = Pubkey::new_unique();
+    let split_recipient = Pubkey::new_unique();
+
+    assert_eq!(
+        process_events(
+            &mut banks_client,
+            &payer,
+            &[instruction::enter(
+                id(),
+                vote_account,
+                payer.pubkey(),
+                withdraw_authority,
+                rewards_authority,
+            )],
+            &[&authorized_withdrawer_keypair],
+        )
+        .await,
+        vec![RewardsVaultEvent::Enter {
+            vote_account,
+            withdraw_authority,
+            rewards_authority,
+            reserve_lamports: 0,
+            epoch,
+        }]
+    );
+
+    assert_eq!(
+        process_events(
+            &mut banks_client,
+            &payer,
+            &[
+                system_instruction::transfer(&payer.pubkey(), &vote_account, 12345678),
+                instruction::withdraw_rewards(
+                    id(),
+                    vote_account,
+                    rewards_recipient,
+                    rewards_authority,
+                ),
+            ],
+            &[&rewards_authority_keypair],
+        )
+        .await,
+        vec![RewardsVaultEvent::WithdrawRewards {
+            vote_account,
+            rewards_recipient,
+            lamports: 12345678,
+            epoch,
+        }]
+    );
+
+    assert_eq!(
+        process_events(
+            &mut banks_client,
+            &payer,
+            &[
+                instruction::set_reserve(id(), vote_account, withdraw_authority, 1_000_000),
+                instruction::set_rewards_recipient(
+                    id(),
+                    vote_account,
+                    withdraw_authority,
+                    Some(rewards_recipient),
+                ),
+                instruction::set_rewards_recipient(id(), vote_account, withdraw_authority, None),
+                instruction::set_crank_mode(id(), vote_account, withdraw_authority, true),
+                instruction::set_withdraw_limit(id(), vote_account, withdraw_authority, 5_000_000),
+                instruction::set_split(
+                    id(),
+                    vote_account,
+                    withdraw_authority,
+                    &[(rewards_recipient, 7_500), (split_recipient, 2_500)],
+                ),
+                instruction::update_commission(id(), vote_account, withdraw_authority, 42),
+            ],
+            &[&authorized_withdrawer_keypair],
+        )
+        .await,
+        vec![
+            RewardsVaultEvent::SetReserve {
+                vote_account,
+                reserve_lamports: 1_000_000,
+                epoch,
+            },
+            RewardsVaultEvent::SetRewardsRecipient {
+                vote_account,
+                rewards_recipient,
+                epoch,
+            },
+            RewardsVaultEvent::SetRewardsRecipient {
+                vote_account,
+                rewards_recipient: Pubkey::default(),
+                epoch,
+            },
+            RewardsVaultEvent::SetCrankMode {
+                vote_account,
+                crank_enabled: true,
+                epoch,
+            },
+            RewardsVaultEvent::SetWithdrawLimit {
+                vote_account,
+                max_withdraw_per_epoch: 5_000_000,
+                epoch,
+            },
+            RewardsVaultEvent::SetSplit {
+                vote_account,
+                split_recipients: [
+                    rewards_recipient,
+                    split_recipient,
+                    Pubkey::default(),
+                    Pubkey::default(),
+                ],
+                split_bps: [7_500, 2_500, 0, 0],
+                epoch,
+            },
+            RewardsVaultEvent::UpdateCommission {
+                vote_account,
+                commission: 42,
+                epoch,
+            },
+        ]
+    );
+
+    assert_eq!(
+        process_events(
+            &mut banks_client,
+            &payer,
+            &[instruction::set_rewards_authority(
+                id(),
+                vote_account,
+                withdraw_authority,
+                new_rewards_authority,
+            )],
+            &[&authorized_withdrawer_keypair],
+        )
+        .await,
+        vec![RewardsVaultEvent::SetRewardsAuthority {
+            vote_account,
+            rewards_authority: new_rewards_authority,
+            epoch,
+        }]
+    );
+
+    assert_eq!(
+        process_events(
+            &mut banks_client,
+            &payer,
+            &[instruction::leave(
+                id(),
+                vote_account,
+                payer.pubkey(),
+                withdraw_authority,
+            )],
+            &[&authorized_withdrawer_keypair],
+        )
+        .await,
+        vec![RewardsVaultEvent::Leave {
+            vote_account,
+            withdraw_authority,
+            epoch,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_migrate_event() {
+    let mut context = ProgramTest::new("sol_rewards_vault_program", id(), None)
+        .start_with_context()
+        .await;
+    let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+
+    let epoch = context
+        .banks_client
+        .get_sysvar::<solana_program::clock::Clock>()
+        .await
+        .unwrap()
+        .epoch;
+
+    let (vote_account_keypair, authorized_withdrawer_keypair) =
+        create_vote_account(&mut context.banks_client, &payer).await;
+    let vote_account = vote_account_keypair.pubkey();
+
+    let rent = context.banks_client.get_rent().await.unwrap();
+    context.set_account(
+        &get_rewards_vault_address(&id(), &vote_account),
+        &AccountSharedData::from(Account {
+            lamports: rent.minimum_balance(RewardsVaultStateV0::size_of()),
+            data: bytemuck::bytes_of(&RewardsVaultStateV0 {
+                original_withdraw_authority: authorized_withdrawer_keypair.pubkey(),
+                rewards_authority: Pubkey::new_unique(),
+            })
+            .to_vec(),
+            owner: id(),
+            ..Account::default()
+        }),
+    );
+
+    assert_eq!(
+        process_events(
+            &mut context.banks_client,
+            &payer,
+            &[instruction::migrate(id(), vote_account, payer.pubkey())],
+            &[],
+        )
+        .await,
+        vec![RewardsVaultEvent::Migrate {
+            vote_account,
+            previous_version: 0,
+            version: RewardsVaultState::VERSION,
+            epoch,
+        }]
+    );
+}